@@ -3,52 +3,314 @@
 
 /* auto-generated by NAPI-RS */
 
+/** An authorization request passed to the hook of `Database.authorizer()`. */
+export interface AuthorizerArgs {
+  /** The name of the SQLite action code, such as `SQLITE_READ`. */
+  action: string
+  /** The table being accessed, if the action concerns a table. */
+  table?: string
+  /** The column being read or updated, for `SQLITE_READ` and `SQLITE_UPDATE`. */
+  column?: string
+  /** The name of the database being accessed, such as `main` or `temp`. */
+  database?: string
+  /** The innermost trigger or view responsible for the access, if any. */
+  accessor?: string
+}
+/**
+ * A rule of an `AuthorizerPolicy`, which matches the authorization requests
+ * whose given fields are all equal to those of the rule.
+ */
+export interface AuthorizerRule {
+  /** The name of the SQLite action code, such as `SQLITE_READ`. */
+  action?: string
+  table?: string
+  column?: string
+  database?: string
+}
+/**
+ * A declarative authorizer, evaluated without calling into JS. Requests
+ * matching a `deny` rule are denied, then those matching an `ignore` rule
+ * are ignored, then those matching an `allow` rule are allowed, and the
+ * remaining ones get the `default` result, which defaults to `"allow"`.
+ */
+export interface AuthorizerPolicy {
+  allow?: Array<AuthorizerRule>
+  deny?: Array<AuthorizerRule>
+  ignore?: Array<AuthorizerRule>
+  default?: string
+}
 export interface Options {
   timeout?: number
+  authToken?: string
+  syncUrl?: string
+  /** The interval between periodic syncs of an embedded replica, in seconds. */
+  syncPeriod?: number
+  encryptionKey?: string | Buffer
+  /** The cipher used with `encryptionKey`, which defaults to `aes256cbc`. */
+  encryptionCipher?: string
+  /** Opens a local database without write access. */
+  readonly?: boolean
+  /** Fails with `SQLITE_CANTOPEN` instead of creating a missing database. */
+  fileMustExist?: boolean
+  /**
+   * The `OpenFlags` of a local database, which replace those derived from
+   * `readonly` and `fileMustExist`. The threading and cache modes of the
+   * connection cannot be set.
+   */
+  flags?: number
+  /**
+   * Called asynchronously with the SQL of each statement run on a local
+   * database, with its parameters expanded.
+   */
+  verbose?: (sql: string) => void
+}
+/** Flags for opening a local database, combined in the `flags` option. */
+export const enum OpenFlags {
+  ReadOnly = 1,
+  ReadWrite = 2,
+  Create = 4,
+  Uri = 64,
+  Memory = 128,
+  NoFollow = 16777216
+}
+export interface SyncResult {
+  frames_synced: number
+  frame_no?: number
+}
+/**
+ * The events reported to the hook of `Database.trace()`, which default to
+ * `statement` and `profile`.
+ */
+export interface TraceOptions {
+  /** Reports each statement as it starts running. */
+  statement?: boolean
+  /** Reports each statement as it finishes, along with its duration. */
+  profile?: boolean
+  /** Reports each row returned by a statement. */
+  row?: boolean
+}
+/** An event reported to the hook of `Database.trace()`. */
+export interface TraceEvent {
+  /** `statement`, `profile` or `row`. */
+  type: string
+  /** The SQL of the statement. */
+  sql: string
+  /**
+   * The SQL of the statement with its parameters expanded, for `statement`
+   * events.
+   */
+  expandedSql?: string
+  /** The duration of the statement in seconds, for `profile` events. */
+  duration?: number
+}
+export interface FunctionOptions {
+  deterministic?: boolean
+  varargs?: boolean
+  directOnly?: boolean
+  safeIntegers?: boolean
+}
+export interface BackupProgress {
+  totalPages: number
+  remainingPages: number
 }
 export interface RunResult {
   changes: number
   duration: number
-  lastInsertRowid: number
+  /** The rowid of the last inserted row, as a BigInt with safe integers. */
+  lastInsertRowid: number | bigint
+  /**
+   * The rows returned by a statement with a `RETURNING` clause. Other
+   * statements that return data are stepped through without keeping them.
+   */
+  rows?: unknown[]
 }
+/** The error thrown for failed SQLite and libSQL operations. */
 export declare class SqliteError {
   message: string
   code: string
+  /** The SQLite result code, as reported by libSQL. */
   rawCode: number
+  /** The extended SQLite result code. */
+  extendedCode: number
+  /**
+   * The byte offset of the token that caused the error in the SQL being
+   * prepared, if any.
+   */
+  offset?: number
+  constructor(message: string, code: string, rawCode?: number | undefined | null)
 }
-export declare class AuthorizerArgs { }
 export declare class Database {
   /**
-   * Only supports arity-1 (callback style) JS hooks: cb => cb("allow")
-   * This is required due to napi v2 threading restrictions.
+   * Sets a JS hook that authorizes each action of the statements being
+   * prepared, returning `"allow"`, `"deny"` or `"ignore"`. Any other
+   * result, or a hook that throws, denies the action. Asynchronous
+   * queries cannot call the hook, so connections running them only
+   * accept a policy.
+   *
+   * A policy authorizes actions natively instead, and `null` removes the
+   * authorizer.
+   */
+  authorizer(authorizer: ((args: AuthorizerArgs) => 'allow' | 'deny' | 'ignore') | AuthorizerPolicy | null): void
+  /**
+   * Sets a JS hook that is called with the statements run on the
+   * connection, including those of asynchronous queries. Events are
+   * delivered asynchronously, in order, and `null` removes the hook, as
+   * well as the `verbose` one.
+   */
+  trace(hook: ((event: TraceEvent) => void) | null, options?: TraceOptions): void
+  /**
+   * Pulls the frames written to the primary database since the last sync
+   * into the embedded replica.
+   */
+  sync(): SyncResult
+  /** Syncs the embedded replica until it reaches `replication_index`. */
+  syncUntil(replicationIndex: number): SyncResult
+  /**
+   * Syncs the embedded replica off the JS thread, resolving to the same
+   * result as `sync()`.
    */
-  authorizer(hook: (...args: any[]) => any): void
+  syncAsync(): Promise<SyncResult>
+  /**
+   * Syncs the embedded replica until it reaches `replication_index` off
+   * the JS thread, resolving to the same result as `syncUntil()`.
+   */
+  syncUntilAsync(replicationIndex: number): Promise<SyncResult>
   get memory(): boolean
+  /** The path or URL the database was opened with. */
+  get name(): string
+  /** Whether the main database of the connection is read-only. */
+  get readonly(): boolean
   constructor(path: string, opts?: Options | undefined | null)
   inTransaction(): boolean
   prepare(sql: string): Statement
-  pragma(): void
-  backup(): void
-  serialize(): void
-  function(): void
-  aggregate(): void
-  table(): void
-  loadExtension(path: string): void
-  maxWriteReplicationIndex(): void
+  /**
+   * Prepares a statement off the JS thread, resolving to the prepared
+   * statement.
+   */
+  prepareAsync(sql: string): Promise<Statement>
+  /**
+   * Runs a pragma, returning its rows, or the first value of its first row
+   * in `simple` mode. `source` is the pragma name, optionally qualified by
+   * a schema name and followed by its argument, such as
+   * `main.journal_mode = WAL`.
+   */
+  pragma(source: string, simple?: boolean | undefined | null): unknown
+  /**
+   * Runs a pragma off the JS thread, resolving to the same value as
+   * `pragma()`.
+   */
+  pragmaAsync(source: string, simple?: boolean | undefined | null): Promise<unknown>
+  /**
+   * Starts an online backup of the `attached` database into the file at
+   * `filename`, whose pages are then copied with `Backup.transfer()`.
+   */
+  backup(attached: string, filename: string): Backup
+  /** Returns the image of the `attached` database as a buffer. */
+  serialize(attached: string): Buffer
+  /**
+   * Replaces the `attached` database with an in-memory copy of the image in
+   * `data`, as returned by `serialize()`.
+   */
+  deserialize(data: Buffer, attached: string, readonly: boolean): void
+  /** Registers a user-defined scalar SQL function backed by a JS callback. */
+  function(name: string, options: FunctionOptions, callback: (...args: any[]) => any): void
+  /**
+   * Registers a user-defined aggregate SQL function backed by JS callbacks.
+   * Supplying `inverse` makes the aggregate usable as a window function.
+   */
+  aggregate(name: string, options: FunctionOptions, start: unknown, step: (...args: any[]) => any, inverse?: ((...args: any[]) => any) | undefined | null, result?: ((...args: any[]) => any) | undefined | null): void
+  /**
+   * Registers a virtual table module backed by a JS factory, which returns
+   * the table definition for the arguments of each table created with it.
+   * Eponymous modules are used directly as table-valued functions.
+   */
+  table(name: string, factory: (...args: any[]) => any, eponymous: boolean): void
+  /** Toggles loading extensions with `loadExtension()`, which is disabled by default. */
+  enableLoadExtension(toggle?: boolean | undefined | null): void
+  loadExtension(path: string, entryPoint?: string | undefined | null): void
+  /**
+   * Re-encrypts the database with a new key and cipher, which also encrypts
+   * a plaintext database. An empty or missing key decrypts the database.
+   */
+  rekey(key?: string | Buffer | undefined | null, cipher?: string | undefined | null): void
+  /**
+   * Returns the highest replication index of the writes made through this
+   * database, which other replicas can wait for with `syncUntil()`.
+   */
+  maxWriteReplicationIndex(): number | null
   exec(sql: string): void
+  /** Executes SQL statements off the JS thread. */
+  execAsync(sql: string): Promise<void>
+  /**
+   * Aborts the queries running on the connection, which fail with
+   * `SQLITE_INTERRUPT`, along with its queued async tasks.
+   */
   interrupt(): void
   close(): void
   defaultSafeIntegers(toggle?: boolean | undefined | null): void
   unsafeMode(): void
 }
+/**
+ * An online backup in progress, which copies pages from a source database
+ * into a destination file.
+ */
+export declare class Backup {
+  /**
+   * Copies up to `pages` pages to the destination, or all remaining pages
+   * if `pages` is negative. Busy or locked source databases are retried on
+   * the next transfer.
+   */
+  transfer(pages: number): BackupProgress
+  /** Finishes the backup and closes the destination database. */
+  close(): void
+}
 export declare class Statement {
   columns(): unknown[]
   iterate(params?: unknown | undefined | null): object
   run(params?: unknown | undefined | null): RunResult
+  /**
+   * Executes the statement off the JS thread, resolving to the same info
+   * object as `run()`.
+   */
+  runAsync(params?: unknown | undefined | null): Promise<RunResult>
+  /**
+   * Queries the statement off the JS thread, resolving to the same
+   * rows as `all()`.
+   */
+  allAsync(params?: unknown | undefined | null): Promise<unknown>
+  /**
+   * Queries the statement off the JS thread, resolving to the same
+   * row as `get()`.
+   */
+  getAsync(params?: unknown | undefined | null): Promise<unknown>
   all(params?: unknown | undefined | null): unknown[]
   pluck(pluck?: boolean | undefined | null): this
   raw(raw?: boolean | undefined | null): this
   get(params?: unknown | undefined | null): unknown
+  /** The SQL text the statement was prepared from. */
+  get source(): string
+  /**
+   * Whether the statement returns data, such as a `SELECT` or a statement
+   * with a `RETURNING` clause.
+   */
+  get reader(): boolean
+  /** Whether the statement makes no direct changes to the database file. */
+  get readonly(): boolean
+  /**
+   * Returns the SQL text of the statement with its parameters replaced by
+   * the bound parameters, or the given ones, as SQL literals.
+   */
+  expandedSql(params?: unknown | undefined | null): string
+  /**
+   * Permanently binds parameters to the statement, which are then used by
+   * every execution and can no longer be overridden.
+   */
+  bind(params?: unknown | undefined | null): this
   safeIntegers(toggle?: boolean | undefined | null): this
+  /**
+   * Aborts the queries running on the statement's connection, which fail
+   * with `SQLITE_INTERRUPT`.
+   */
+  interrupt(): void
 }
 export declare class StatementRows { }
@@ -310,10 +310,11 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { SqliteError, AuthorizerArgs, Database, Statement, StatementRows } = nativeBinding
+const { SqliteError, Database, OpenFlags, Backup, Statement, StatementRows } = nativeBinding
 
 module.exports.SqliteError = SqliteError
-module.exports.AuthorizerArgs = AuthorizerArgs
 module.exports.Database = Database
+module.exports.OpenFlags = OpenFlags
+module.exports.Backup = Backup
 module.exports.Statement = Statement
 module.exports.StatementRows = StatementRows
@@ -47,18 +47,35 @@ test.serial("Statement.run() [positional]", async (t) => {
   const db = t.context.db;
 
   const stmt = await db.prepare("INSERT INTO users(name, email) VALUES (?, ?)");
-  const info = stmt.run(["Carol", "carol@example.net"]);
+  const info = await stmt.run(["Carol", "carol@example.net"]);
   t.is(info.changes, 1);
   t.is(info.lastInsertRowid, 3);
 });
 
+test.serial("Statement.run() [returning]", async (t) => {
+  const db = t.context.db;
+
+  const stmt = await db.prepare("INSERT INTO users (id, name) VALUES (?, ?) RETURNING id");
+  await t.throwsAsync(() => stmt.run([1, "Eve"]), {
+    instanceOf: t.context.errorType,
+    code: "SQLITE_CONSTRAINT_PRIMARYKEY"
+  });
+  stmt.safeIntegers();
+  const info = await stmt.run([3, "Eve"]);
+  t.is(info.changes, 1);
+  t.is(info.lastInsertRowid, 3n);
+  t.deepEqual(info.rows, [{ id: 3n }]);
+  const select = await db.prepare("SELECT 1");
+  t.is((await select.run()).rows, undefined);
+});
+
 test.serial("Statement.get() [no parameters]", async (t) => {
   const db = t.context.db;
 
   var stmt = 0;
 
   stmt = await db.prepare("SELECT * FROM users");
-  t.is(stmt.get().name, "Alice");
+  t.is((await stmt.get()).name, "Alice");
   t.deepEqual(await stmt.raw().get(), [1, 'Alice', 'alice@example.org']);
 });
 
@@ -68,15 +85,15 @@ test.serial("Statement.get() [positional]", async (t) => {
   var stmt = 0;
 
   stmt = await db.prepare("SELECT * FROM users WHERE id = ?");
-  t.is(stmt.get(0), undefined);
-  t.is(stmt.get([0]), undefined);
-  t.is(stmt.get(1).name, "Alice");
-  t.is(stmt.get(2).name, "Bob");
+  t.is(await stmt.get(0), undefined);
+  t.is(await stmt.get([0]), undefined);
+  t.is((await stmt.get(1)).name, "Alice");
+  t.is((await stmt.get(2)).name, "Bob");
 
   stmt = await db.prepare("SELECT * FROM users WHERE id = ?1");
-  t.is(stmt.get({1: 0}), undefined);
-  t.is(stmt.get({1: 1}).name, "Alice");
-  t.is(stmt.get({1: 2}).name, "Bob");
+  t.is(await stmt.get({1: 0}), undefined);
+  t.is((await stmt.get({1: 1})).name, "Alice");
+  t.is((await stmt.get({1: 2})).name, "Bob");
 });
 
 test.serial("Statement.get() [named]", async (t) => {
@@ -85,19 +102,19 @@ test.serial("Statement.get() [named]", async (t) => {
   var stmt = undefined;
 
   stmt = await db.prepare("SELECT * FROM users WHERE id = :id");
-  t.is(stmt.get({ id: 0 }), undefined);
-  t.is(stmt.get({ id: 1 }).name, "Alice");
-  t.is(stmt.get({ id: 2 }).name, "Bob");
+  t.is(await stmt.get({ id: 0 }), undefined);
+  t.is((await stmt.get({ id: 1 })).name, "Alice");
+  t.is((await stmt.get({ id: 2 })).name, "Bob");
 
   stmt = await db.prepare("SELECT * FROM users WHERE id = @id");
-  t.is(stmt.get({ id: 0 }), undefined);
-  t.is(stmt.get({ id: 1 }).name, "Alice");
-  t.is(stmt.get({ id: 2 }).name, "Bob");
+  t.is(await stmt.get({ id: 0 }), undefined);
+  t.is((await stmt.get({ id: 1 })).name, "Alice");
+  t.is((await stmt.get({ id: 2 })).name, "Bob");
 
   stmt = await db.prepare("SELECT * FROM users WHERE id = $id");
-  t.is(stmt.get({ id: 0 }), undefined);
-  t.is(stmt.get({ id: 1 }).name, "Alice");
-  t.is(stmt.get({ id: 2 }).name, "Bob");
+  t.is(await stmt.get({ id: 0 }), undefined);
+  t.is((await stmt.get({ id: 1 })).name, "Alice");
+  t.is((await stmt.get({ id: 2 })).name, "Bob");
 });
 
 
@@ -105,7 +122,30 @@ test.serial("Statement.get() [raw]", async (t) => {
   const db = t.context.db;
 
   const stmt = await db.prepare("SELECT * FROM users WHERE id = ?");
-  t.deepEqual(stmt.raw().get(1), [1, "Alice", "alice@example.org"]);
+  t.deepEqual(await stmt.raw().get(1), [1, "Alice", "alice@example.org"]);
+});
+
+test.serial("Statement.readonly", async (t) => {
+  const db = t.context.db;
+  const select = await db.prepare("SELECT * FROM users");
+  const insert = await db.prepare("INSERT INTO users (name) VALUES (?)");
+  t.true(select.readonly);
+  t.false(insert.readonly);
+});
+
+test.serial("Statement.bind()", async (t) => {
+  const db = t.context.db;
+
+  const stmt = (await db.prepare("SELECT name FROM users WHERE id = ?")).bind(2);
+  t.is((await stmt.get()).name, "Bob");
+  t.deepEqual(await stmt.all(), [{ name: "Bob" }]);
+  await t.throwsAsync(() => stmt.run(1), {
+    instanceOf: TypeError,
+    message: "This statement already has bound parameters"
+  });
+
+  const pair = (await db.prepare("SELECT name FROM users WHERE id = ? OR name = ? ORDER BY id")).bind(1, "Bob");
+  t.deepEqual(await pair.all(), [{ name: "Alice" }, { name: "Bob" }]);
 });
 
 test.serial("Statement.iterate() [empty]", async (t) => {
@@ -127,6 +167,18 @@ test.serial("Statement.iterate()", async (t) => {
   }
 });
 
+test.serial("Statement.iterate() [for await]", async (t) => {
+  const db = t.context.db;
+
+  const stmt = await db.prepare("SELECT * FROM users");
+  const expected = [1, 2];
+  var idx = 0;
+  for await (const row of await stmt.iterate()) {
+    t.is(row.id, expected[idx++]);
+  }
+  t.is(idx, 2);
+});
+
 test.serial("Statement.all()", async (t) => {
   const db = t.context.db;
 
@@ -241,9 +293,9 @@ test.serial("Database.transaction()", async (t) => {
     "INSERT INTO users(name, email) VALUES (:name, :email)"
   );
 
-  const insertMany = db.transaction((users) => {
+  const insertMany = db.transaction(async (users) => {
     t.is(db.inTransaction, true);
-    for (const user of users) insert.run(user);
+    for (const user of users) await insert.run(user);
   });
 
   t.is(db.inTransaction, false);
@@ -255,9 +307,9 @@ test.serial("Database.transaction()", async (t) => {
   t.is(db.inTransaction, false);
 
   const stmt = await db.prepare("SELECT * FROM users WHERE id = ?");
-  t.is(stmt.get(3).name, "Joey");
-  t.is(stmt.get(4).name, "Sally");
-  t.is(stmt.get(5).name, "Junior");
+  t.is((await stmt.get(3)).name, "Joey");
+  t.is((await stmt.get(4)).name, "Sally");
+  t.is((await stmt.get(5)).name, "Junior");
 });
 
 test.serial("Database.transaction().immediate()", async (t) => {
@@ -265,9 +317,9 @@ test.serial("Database.transaction().immediate()", async (t) => {
   const insert = await db.prepare(
     "INSERT INTO users(name, email) VALUES (:name, :email)"
   );
-  const insertMany = db.transaction((users) => {
+  const insertMany = db.transaction(async (users) => {
     t.is(db.inTransaction, true);
-    for (const user of users) insert.run(user);
+    for (const user of users) await insert.run(user);
   });
   t.is(db.inTransaction, false);
   await insertMany.immediate([
@@ -282,6 +334,17 @@ test.serial("Database.pragma()", async (t) => {
   const db = t.context.db;
   await db.pragma("cache_size = 2000");
   t.deepEqual(await db.pragma("cache_size"), [{ "cache_size": 2000 }]);
+  t.is(await db.pragma("cache_size", { simple: true }), 2000);
+  await t.throwsAsync(db.pragma("cache_size; DROP TABLE users"), {
+    instanceOf: TypeError,
+    message: "Invalid pragma: cache_size; DROP TABLE users",
+  });
+  await t.throwsAsync(db.pragma("cache_size = 10; DROP TABLE users"), {
+    instanceOf: TypeError,
+    message: "Invalid pragma: cache_size = 10; DROP TABLE users",
+  });
+  const count = await db.prepare("SELECT count(*) FROM users");
+  t.is((await count.raw().get())[0], 2);
 });
 
 test.serial("errors", async (t) => {
@@ -357,6 +420,100 @@ test.serial("Statement.interrupt()", async (t) => {
   });
 });
 
+test.serial("Database.function() [async query]", async (t) => {
+  const db = t.context.db;
+  db.function("double", (x) => x * 2);
+  const stmt = await db.prepare("SELECT double(id) AS x FROM users");
+  await t.throwsAsync(async () => {
+    await stmt.all();
+  }, {
+    instanceOf: t.context.errorType,
+    message: "JavaScript functions cannot be called by asynchronous queries",
+  });
+});
+
+test.serial("Database.aggregate() [async query]", async (t) => {
+  const db = t.context.db;
+  db.aggregate("sum_ids", {
+    start: 0,
+    step: (total, id) => total + id,
+  });
+  const stmt = await db.prepare("SELECT sum_ids(id) FROM users");
+  await t.throwsAsync(stmt.get(), {
+    instanceOf: t.context.errorType,
+    message: "JavaScript functions cannot be called by asynchronous queries",
+  });
+});
+
+test.serial("Database.table() [async query]", async (t) => {
+  const db = t.context.db;
+  db.table("split", {
+    columns: ["part"],
+    rows: function* (str, sep) {
+      for (const part of str.split(sep)) yield [part];
+    },
+  });
+  await t.throwsAsync(db.prepare("SELECT part FROM split('a,b,c', ',')"), {
+    instanceOf: t.context.errorType,
+    message: "JavaScript functions cannot be called by asynchronous queries",
+  });
+});
+
+test.serial("Database.authorizer() [async query]", async (t) => {
+  const db = t.context.db;
+  const message = "JavaScript authorizers cannot be used with asynchronous queries, use an authorizer policy instead";
+  const running = db.exec(
+    "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c WHERE x < 2000000) SELECT count(*) FROM c"
+  );
+  t.throws(() => {
+    db.authorizer(() => "allow");
+  }, {
+    instanceOf: TypeError,
+    message,
+  });
+  await running;
+  db.authorizer({ deny: [{ column: "email" }] });
+  t.is((await (await db.prepare("SELECT name FROM users")).all()).length, 2);
+  await t.throwsAsync(db.prepare("SELECT email FROM users"), {
+    instanceOf: t.context.errorType,
+    message: "Authorization denied by authorizer policy",
+    code: "SQLITE_AUTH",
+  });
+  db.authorizer(null);
+
+  const [fresh] = await connect(":memory:");
+  fresh.authorizer(() => "allow");
+  await t.throwsAsync(fresh.prepare("SELECT 1"), {
+    instanceOf: TypeError,
+    message,
+  });
+  fresh.close();
+});
+
+test.serial("Database.authorizer() [after async query]", async (t) => {
+  const db = t.context.db;
+  t.is((await (await db.prepare("SELECT name FROM users")).all()).length, 2);
+  db.authorizer(() => "allow");
+  await t.throwsAsync(db.prepare("SELECT 1"), {
+    instanceOf: TypeError,
+    message: "JavaScript authorizers cannot be used with asynchronous queries, use an authorizer policy instead",
+  });
+  db.authorizer(null);
+  t.is((await (await db.prepare("SELECT name FROM users")).all()).length, 2);
+});
+
+test.serial("Database.sync() [not a replica]", async (t) => {
+  const db = t.context.db;
+  const result = db.sync();
+  t.true(result instanceof Promise);
+  await t.throwsAsync(result, { instanceOf: t.context.errorType });
+  await t.throwsAsync(db.syncUntil(1), { instanceOf: t.context.errorType });
+  await t.throwsAsync(() => db.syncUntil("1"), {
+    instanceOf: TypeError,
+    message: "Expected first argument to be a number",
+  });
+});
+
 test.serial("Timeout option", async (t) => {
   const timeout = 1000;
   const path = genDatabaseFilename();
@@ -1,4 +1,6 @@
 import test from "ava";
+import crypto from "crypto";
+import fs from "fs";
 
 test.serial("Statement.run() returning duration", async (t) => {
   const db = t.context.db;
@@ -20,7 +22,7 @@ test.serial("Statement.get() returning duration", async (t) => {
 
 test.serial("Database.authorizer() [allow]", async (t) => {
   const db = t.context.db;
-  db.authorizer(auth => auth("allow"));
+  db.authorizer(() => "allow");
   const stmt = db.prepare("SELECT * FROM users");
   const rows = stmt.all();
   t.is(rows.length, 2);
@@ -28,7 +30,7 @@ test.serial("Database.authorizer() [allow]", async (t) => {
 
 test.serial("Database.authorizer() [ignore]", async (t) => {
   const db = t.context.db;
-  db.authorizer(auth => auth("ignore"));
+  db.authorizer(() => "ignore");
   const stmt = db.prepare("SELECT * FROM users");
   const rows = stmt.all();
   t.is(rows.length, 0);
@@ -36,25 +38,213 @@ test.serial("Database.authorizer() [ignore]", async (t) => {
 
 test.serial("Database.authorizer() [deny]", async (t) => {
   const db = t.context.db;
-  db.authorizer(auth => auth("deny"));
-  let error = null;
-  try {
-    await db.prepare("SELECT * FROM users");
-  } catch (err) {
-    error = err;
-  }
-  t.truthy(error, "Expected an error to be thrown");
-
-  let parsed;
-  try {
-    parsed = JSON.parse(error.message);
-  } catch {
-    t.fail("Error message is not valid JSON: " + String(error && error.message));
-  }
-  t.is(parsed.code, "SQLITE_AUTH");
-  t.is(parsed.libsqlError, true);
-  t.is(parsed.message, "Authorization denied by JS authorizer");
-  t.is(parsed.rawCode, 23);
+  db.authorizer(() => "deny");
+  const error = t.throws(() => {
+    db.prepare("SELECT * FROM users");
+  }, {
+    instanceOf: t.context.errorType,
+    message: "Authorization denied by JS authorizer",
+    code: "SQLITE_AUTH"
+  });
+  t.is(error.rawCode, 23);
+});
+
+test.serial("Database.authorizer() [per action]", async (t) => {
+  const db = t.context.db;
+  const requests = [];
+  db.authorizer((args) => {
+    requests.push(args);
+    return args.table === "users" && args.column === "email" ? "deny" : "allow";
+  });
+  t.deepEqual(db.prepare("SELECT name FROM users").raw().all(), [["Alice"], ["Bob"]]);
+  t.deepEqual(requests, [
+    { action: "SQLITE_SELECT" },
+    { action: "SQLITE_READ", table: "users", column: "name", database: "main" },
+  ]);
+  t.throws(() => {
+    db.prepare("SELECT email FROM users");
+  }, {
+    instanceOf: t.context.errorType,
+    code: "SQLITE_AUTH"
+  });
+
+  db.exec("CREATE VIEW names AS SELECT name FROM users");
+  requests.length = 0;
+  db.prepare("SELECT * FROM names").all();
+  t.true(requests.some((args) => args.action === "SQLITE_READ" && args.table === "users" && args.accessor === "names"));
+
+  db.authorizer((args) => args.action === "SQLITE_READ" && args.column === "email" ? "ignore" : "allow");
+  t.deepEqual(db.prepare("SELECT email FROM users").pluck().all(), [null, null]);
+
+  db.authorizer(() => {
+    throw new Error("boom");
+  });
+  t.throws(() => {
+    db.prepare("SELECT 1");
+  }, {
+    message: "boom"
+  });
+});
+
+test.serial("Database.authorizer() [null]", async (t) => {
+  const db = t.context.db;
+  db.authorizer(() => "deny");
+  t.throws(() => {
+    db.prepare("SELECT * FROM users");
+  }, {
+    instanceOf: t.context.errorType,
+    code: "SQLITE_AUTH"
+  });
+  db.authorizer(null);
+  t.is(db.prepare("SELECT * FROM users").all().length, 2);
+});
+
+test.serial("Database.authorizer() [policy]", async (t) => {
+  const db = t.context.db;
+  db.authorizer({
+    deny: [{ action: "SQLITE_DELETE" }, { table: "users", column: "email" }],
+    ignore: [{ action: "SQLITE_READ", column: "name" }],
+  });
+  t.deepEqual(db.prepare("SELECT id, name FROM users").raw().all(), [[1, null], [2, null]]);
+  t.throws(() => {
+    db.prepare("SELECT email FROM users");
+  }, {
+    instanceOf: t.context.errorType,
+    message: "Authorization denied by authorizer policy",
+    code: "SQLITE_AUTH"
+  });
+  t.throws(() => {
+    db.prepare("DELETE FROM users");
+  }, {
+    instanceOf: t.context.errorType,
+    code: "SQLITE_AUTH"
+  });
+
+  db.authorizer({
+    allow: [{ action: "SQLITE_SELECT" }, { action: "SQLITE_READ", table: "USERS" }],
+    default: "deny",
+  });
+  t.deepEqual(db.prepare("SELECT id FROM users").pluck().all(), [1, 2]);
+  t.throws(() => {
+    db.prepare("SELECT name FROM sqlite_schema");
+  }, {
+    instanceOf: t.context.errorType,
+    code: "SQLITE_AUTH"
+  });
+
+  t.throws(() => {
+    db.authorizer({ deny: [{ action: "SQLITE_NOPE" }] });
+  }, {
+    message: "Unknown authorizer action: SQLITE_NOPE"
+  });
+  t.throws(() => {
+    db.authorizer({ default: "maybe" });
+  }, {
+    message: "Unknown authorization result: maybe"
+  });
+  db.authorizer(null);
+});
+
+test.serial("Database.loadExtension() [disabled]", async (t) => {
+  const db = t.context.db;
+  t.throws(() => {
+    db.loadExtension("./missing-extension");
+  }, {
+    instanceOf: t.context.errorType,
+    message: "not authorized",
+    code: "SQLITE_ERROR"
+  });
+});
+
+test.serial("Database.loadExtension() [missing]", async (t) => {
+  const db = t.context.db;
+  db.enableLoadExtension();
+  const error = t.throws(() => {
+    db.loadExtension("./missing-extension", "sqlite3_missing_init");
+  }, {
+    instanceOf: t.context.errorType,
+    code: "SQLITE_ERROR"
+  });
+  t.regex(error.message, /missing-extension/);
+});
+
+test.serial("Database [encryption]", async (t) => {
+  const x = await import("libsql");
+  const path = `encrypted-${crypto.randomBytes(8).toString("hex")}.db`;
+  const db = new x.default(path, { encryptionKey: "secret" });
+  db.exec("CREATE TABLE t (x); INSERT INTO t VALUES ('hello')");
+  db.close();
+  t.false(fs.readFileSync(path).includes("hello"));
+
+  t.throws(() => {
+    new x.default(path, { encryptionKey: "wrong" });
+  }, {
+    instanceOf: t.context.errorType,
+    code: "SQLITE_NOTADB"
+  });
+  t.throws(() => {
+    new x.default(path, { encryptionKey: 42 });
+  }, {
+    instanceOf: TypeError
+  });
+
+  const reopened = new x.default(path, { encryptionKey: Buffer.from("secret"), encryptionCipher: "aes256cbc" });
+  t.is(reopened.prepare("SELECT x FROM t").get().x, "hello");
+  reopened.close();
+  fs.unlinkSync(path);
+});
+
+test.serial("Database.rekey()", async (t) => {
+  const x = await import("libsql");
+  const path = `rekey-${crypto.randomBytes(8).toString("hex")}.db`;
+  const db = new x.default(path);
+  db.exec("CREATE TABLE t (x); INSERT INTO t VALUES ('hello')");
+  db.rekey("first");
+  db.close();
+  t.false(fs.readFileSync(path).includes("hello"));
+
+  const encrypted = new x.default(path, { encryptionKey: "first" });
+  encrypted.rekey(Buffer.from("second"));
+  encrypted.close();
+  t.throws(() => {
+    new x.default(path, { encryptionKey: "first" });
+  }, {
+    instanceOf: t.context.errorType,
+    code: "SQLITE_NOTADB"
+  });
+
+  const rekeyed = new x.default(path, { encryptionKey: "second" });
+  t.is(rekeyed.prepare("SELECT x FROM t").get().x, "hello");
+  rekeyed.rekey(null);
+  rekeyed.close();
+
+  const decrypted = new x.default(path);
+  t.is(decrypted.prepare("SELECT x FROM t").get().x, "hello");
+  decrypted.close();
+  fs.unlinkSync(path);
+});
+
+test.serial("Database.rekey() [trace]", async (t) => {
+  const x = await import("libsql");
+  const path = `rekey-${crypto.randomBytes(8).toString("hex")}.db`;
+  const statements = [];
+  const db = new x.default(path, { verbose: (sql) => statements.push(sql) });
+  const events = [];
+  db.trace((event) => events.push(event));
+  db.exec("CREATE TABLE t (x)");
+  db.rekey("top-secret-key");
+  db.rekey(Buffer.from("another-secret"));
+  await new Promise((resolve) => setTimeout(resolve, 50));
+  const traced = [
+    ...statements,
+    ...events.flatMap((event) => [event.sql, event.expandedSql]),
+  ].join("\n");
+  t.true(traced.includes("CREATE TABLE t (x)"));
+  t.false(traced.includes("top-secret-key"));
+  t.false(traced.includes("another-secret"));
+  t.false(traced.includes(Buffer.from("another-secret").toString("hex")));
+  db.close();
+  fs.unlinkSync(path);
 });
 
 const connect = async (path_opt) => {
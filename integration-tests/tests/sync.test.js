@@ -32,6 +32,93 @@ test.serial("Open in-memory database", async (t) => {
   t.is(db.memory, true);
 });
 
+test.serial("Open read-only database", async (t) => {
+  const path = genDatabaseFilename();
+  const [writer] = await connect(path);
+  writer.exec("CREATE TABLE t(x); INSERT INTO t VALUES (1)");
+  t.is(writer.name, path);
+  t.is(writer.readonly, false);
+  writer.close();
+
+  const [db] = await connect(path, { readonly: true });
+  t.is(db.readonly, true);
+  t.is(db.prepare("SELECT x FROM t").pluck().all()[0], 1);
+  t.throws(() => {
+    db.exec("INSERT INTO t VALUES (2)");
+  }, {
+    instanceOf: t.context.errorType,
+    code: "SQLITE_READONLY"
+  });
+  db.close();
+  fs.unlinkSync(path);
+});
+
+test.serial("Open with fileMustExist", async (t) => {
+  const path = genDatabaseFilename();
+  await t.throwsAsync(connect(path, { fileMustExist: true }), {
+    instanceOf: t.context.errorType,
+    code: "SQLITE_CANTOPEN"
+  });
+  t.false(fs.existsSync(path));
+});
+
+test.serial("Open with URI and flags", async (t) => {
+  if (t.context.provider === 'sqlite') {
+    // better-sqlite3 supports neither file: URIs nor open flags
+    t.assert(true);
+    return;
+  }
+  const { OpenFlags } = await import("libsql");
+  const path = genDatabaseFilename();
+  const [writer] = await connect(path);
+  writer.exec("CREATE TABLE t(x)");
+  writer.close();
+
+  const [uri] = await connect(`file:${path}?mode=ro`);
+  t.is(uri.readonly, true);
+  t.throws(() => {
+    uri.exec("INSERT INTO t VALUES (1)");
+  }, {
+    instanceOf: t.context.errorType,
+    code: "SQLITE_READONLY"
+  });
+  uri.close();
+
+  const [flagged] = await connect(path, { flags: OpenFlags.ReadOnly });
+  t.is(flagged.readonly, true);
+  flagged.close();
+
+  // SQLITE_OPEN_NOMUTEX and SQLITE_OPEN_SHAREDCACHE
+  for (const flags of [OpenFlags.ReadWrite | 0x8000, OpenFlags.ReadWrite | 0x20000]) {
+    await t.throwsAsync(connect(path, { flags }), {
+      instanceOf: TypeError,
+      message: "The flags option cannot set the threading or cache mode of the connection",
+    });
+  }
+  fs.unlinkSync(path);
+});
+
+test.serial("Database [replica options]", async (t) => {
+  if (t.context.provider === 'sqlite') {
+    // better-sqlite3 has no embedded replicas
+    t.assert(true);
+    return;
+  }
+  const options = [
+    ["readonly", { readonly: true }],
+    ["fileMustExist", { fileMustExist: true }],
+    ["flags", { flags: 1 }],
+  ];
+  const path = genDatabaseFilename();
+  for (const [name, option] of options) {
+    await t.throwsAsync(connect(path, { syncUrl: "http://127.0.0.1:9", ...option }), {
+      instanceOf: TypeError,
+      message: `The ${name} option is not supported by embedded replicas`,
+    });
+  }
+  t.false(fs.existsSync(path));
+});
+
 test.serial("Statement.prepare() error", async (t) => {
   const db = t.context.db;
 
@@ -43,12 +130,38 @@ test.serial("Statement.prepare() error", async (t) => {
   });
 });
 
+test.serial("SqliteError codes and offset", async (t) => {
+  const db = t.context.db;
+
+  const prepareError = t.throws(() => {
+    db.prepare("SELECT nosuch FROM users");
+  }, {
+    instanceOf: t.context.errorType,
+    code: "SQLITE_ERROR"
+  });
+  t.true(prepareError instanceof Error);
+  t.is(prepareError.name, "SqliteError");
+  t.is(prepareError.rawCode, 1);
+  t.is(prepareError.offset, 7);
+
+  const constraintError = t.throws(() => {
+    db.exec("INSERT INTO users (id, name, email) VALUES (1, 'Eve', 'eve@example.org')");
+  }, {
+    instanceOf: t.context.errorType,
+    code: "SQLITE_CONSTRAINT_PRIMARYKEY"
+  });
+  t.is(constraintError.rawCode, 1555);
+  t.is(constraintError.extendedCode, 1555);
+  t.is(constraintError.offset, null);
+});
+
 test.serial("Statement.run() returning rows", async (t) => {
   const db = t.context.db;
 
   const stmt = db.prepare("SELECT 1");
   const info = stmt.run();
   t.is(info.changes, 0);
+  t.is(info.rows, undefined);
 });
 
 test.serial("Statement.run() [positional]", async (t) => {
@@ -69,6 +182,55 @@ test.serial("Statement.run() [named]", async (t) => {
   t.is(info.lastInsertRowid, 3);
 });
 
+test.serial("Statement.run() [constraint error]", async (t) => {
+  const db = t.context.db;
+
+  const stmt = db.prepare("INSERT INTO users (id, name) VALUES (?, ?)");
+  t.throws(() => {
+    stmt.run([1, "Eve"]);
+  }, {
+    instanceOf: t.context.errorType,
+    code: "SQLITE_CONSTRAINT_PRIMARYKEY"
+  });
+  t.is(stmt.run([3, "Eve"]).changes, 1);
+});
+
+test.serial("Statement.run() [changes with triggers]", async (t) => {
+  const db = t.context.db;
+
+  db.exec("CREATE TABLE log (id INTEGER)");
+  db.exec("CREATE TRIGGER users_delete AFTER DELETE ON users BEGIN INSERT INTO log VALUES (old.id); INSERT INTO log VALUES (old.id); END");
+  t.is(db.prepare("DELETE FROM users").run().changes, 2);
+  t.is(db.prepare("SELECT count(*) AS count FROM log").get().count, 4);
+  t.is(db.prepare("CREATE TABLE other (x)").run().changes, 0);
+});
+
+test.serial("Statement.run() [returning]", async (t) => {
+  if (t.context.provider === 'sqlite') {
+    // better-sqlite3 does not return rows from run()
+    t.assert(true);
+    return;
+  }
+  const db = t.context.db;
+
+  const info = db.prepare("UPDATE users SET name = upper(name) RETURNING id, name").run();
+  t.is(info.changes, 2);
+  t.deepEqual(info.rows, [{ id: 1, name: "ALICE" }, { id: 2, name: "BOB" }]);
+
+  const stmt = db.prepare("DELETE FROM users WHERE id = ? RETURNING name").pluck();
+  t.deepEqual(stmt.run([2]).rows, ["BOB"]);
+  t.is(db.prepare("INSERT INTO users (name) VALUES ('Carol')").run().rows, undefined);
+});
+
+test.serial("Statement.run() [safe integers]", async (t) => {
+  const db = t.context.db;
+
+  const stmt = db.prepare("INSERT INTO users (id, name) VALUES (?, ?)");
+  t.is(stmt.run([9007199254740991n, "Carol"]).lastInsertRowid, 9007199254740991);
+  stmt.safeIntegers();
+  t.is(stmt.run([9223372036854775807n, "Dave"]).lastInsertRowid, 9223372036854775807n);
+});
+
 test.serial("Statement.get() [no parameters]", async (t) => {
   const db = t.context.db;
 
@@ -147,6 +309,67 @@ test.serial("Statement.iterate()", async (t) => {
   }
 });
 
+test.serial("Statement.iterate() [async]", async (t) => {
+  const db = t.context.db;
+
+  const insert = db.prepare("INSERT INTO users(name, email) VALUES (?, ?)");
+  for (let i = 0; i < 250; i++) {
+    insert.run([`user${i}`, `user${i}@example.org`]);
+  }
+  const stmt = db.prepare("SELECT * FROM users WHERE id > ? ORDER BY id");
+  var id = 1;
+  for await (const row of stmt.iterate(1)) {
+    t.is(row.id, ++id);
+  }
+  t.is(id, 252);
+
+  stmt.raw();
+  const rows = [];
+  for await (const row of stmt.iterate(250)) {
+    rows.push(row);
+  }
+  t.deepEqual(rows, [
+    [251, "user248", "user248@example.org"],
+    [252, "user249", "user249@example.org"],
+  ]);
+});
+
+test.serial("Statement.iterate() [early termination]", async (t) => {
+  const db = t.context.db;
+
+  const stmt = db.prepare("SELECT * FROM users");
+  for (const row of stmt.iterate()) {
+    t.is(row.id, 1);
+    break;
+  }
+  t.is(stmt.all().length, 2);
+
+  const it = stmt.iterate();
+  t.is(it.next().value.id, 1);
+  t.throws(() => stmt.get(), {
+    instanceOf: TypeError,
+    message: "This statement is busy executing a query"
+  });
+  t.throws(() => stmt.iterate(), {
+    instanceOf: TypeError,
+    message: "This statement is busy executing a query"
+  });
+  t.is(it.return().done, true);
+  t.is(it.next().done, true);
+  t.is(stmt.get().id, 1);
+
+  const it2 = stmt.iterate();
+  t.throws(() => it2.throw(new Error("boom")), { message: "boom" });
+  t.is(it2.next().done, true);
+  t.is(stmt.get().id, 1);
+
+  for await (const row of stmt.iterate()) {
+    t.is(row.id, 1);
+    break;
+  }
+  t.is(stmt.all().length, 2);
+});
+
 test.serial("Statement.all()", async (t) => {
   const db = t.context.db;
 
@@ -212,6 +435,74 @@ test.serial("Statement.raw() [failure]", async (t) => {
   });
 });
 
+test.serial("Statement.bind()", async (t) => {
+  const db = t.context.db;
+  const stmt = db.prepare("SELECT name FROM users WHERE id = ?");
+  t.is(stmt.bind(2), stmt);
+  t.is(stmt.get().name, "Bob");
+  t.deepEqual(stmt.all(), [{ name: "Bob" }]);
+  t.deepEqual([...stmt.iterate()], [{ name: "Bob" }]);
+  t.throws(() => {
+    stmt.get(1);
+  }, {
+    instanceOf: TypeError,
+    message: "This statement already has bound parameters"
+  });
+  t.throws(() => {
+    stmt.bind(1);
+  }, {
+    instanceOf: TypeError,
+    message: "The bind() method can only be invoked once per statement object"
+  });
+
+  const insert = db.prepare("INSERT INTO users (name, email) VALUES (:name, :email)");
+  insert.bind({ name: "Carol", email: "carol@example.org" });
+  insert.run();
+  t.is(db.prepare("SELECT email FROM users WHERE name = ?").get("Carol").email, "carol@example.org");
+
+  const positional = db.prepare("SELECT ?, ?, ?").raw().bind("x", 2n, null);
+  t.deepEqual(positional.get(), ["x", 2, null]);
+  const pair = db.prepare("SELECT name FROM users WHERE id = ? OR name = ? ORDER BY id").pluck();
+  t.deepEqual(pair.bind(1, "Bob").all(), ["Alice", "Bob"]);
+});
+
+test.serial("Statement.source, reader and readonly", async (t) => {
+  const db = t.context.db;
+
+  const select = db.prepare("SELECT * FROM users WHERE id = ?");
+  t.is(select.source, "SELECT * FROM users WHERE id = ?");
+  t.true(select.reader);
+  t.true(select.readonly);
+
+  const insert = db.prepare("INSERT INTO users (name) VALUES (?)");
+  t.false(insert.reader);
+  t.false(insert.readonly);
+
+  const returning = db.prepare("DELETE FROM users RETURNING id");
+  t.true(returning.reader);
+  t.false(returning.readonly);
+});
+
+test.serial("Statement.expandedSql()", async (t) => {
+  if (t.context.provider === 'sqlite') {
+    // better-sqlite3 does not support expanded SQL
+    t.assert(true);
+    return;
+  }
+  const db = t.context.db;
+
+  const stmt = db.prepare("SELECT * FROM users WHERE name = :name AND id > :id");
+  t.is(stmt.expandedSql(), "SELECT * FROM users WHERE name = NULL AND id > NULL");
+  t.is(stmt.expandedSql({ name: "O'Brien", id: 1n }), "SELECT * FROM users WHERE name = 'O''Brien' AND id > 1");
+  stmt.bind({ name: "Bob", id: 0n });
+  t.is(stmt.expandedSql(), "SELECT * FROM users WHERE name = 'Bob' AND id > 0");
+  t.is(stmt.get().name, "Bob");
+
+  const value = db.prepare("SELECT ? AS value");
+  t.is(value.expandedSql(["hello"]), "SELECT 'hello' AS value");
+  t.is(value.get().value, null);
+});
+
 test.serial("Statement.run() with array bind parameter", async (t) => {
   const db = t.context.db;
 
@@ -375,6 +666,412 @@ test.serial("Database.pragma()", async (t) => {
   t.deepEqual(db.pragma("cache_size"), [{ "cache_size": 2000 }]);
 });
 
+test.serial("Database.pragma() [simple]", async (t) => {
+  const db = t.context.db;
+  t.deepEqual(db.pragma("foreign_keys = ON"), []);
+  t.is(db.pragma("foreign_keys", { simple: true }), 1);
+  t.is(db.pragma("main.user_version = 7", { simple: true }), undefined);
+  t.is(db.pragma("main.user_version", { simple: true }), 7);
+  t.is(db.pragma("user_version", { simple: true }), 7);
+  t.is(db.pragma("table_info(users)", { simple: true }), 0);
+  t.deepEqual(db.pragma("table_info(users)").map((column) => column.name), ["id", "name", "email"]);
+  t.is(db.pragma("application_id = -5", { simple: true }), undefined);
+  t.is(db.pragma("application_id", { simple: true }), -5);
+  t.is(db.pragma("journal_mode = 'memory'", { simple: true }), "memory");
+  const invalid = [
+    "",
+    "1 = 1",
+    "cache_size; DROP TABLE users",
+    "main.cache.size",
+    "cache_size = 10; DROP TABLE users",
+    "cache_size = 10 20",
+    "table_info(users); DROP TABLE users",
+    "table_info(users)) --",
+    "journal_mode = 'wal'' OR 1",
+  ];
+  for (const source of invalid) {
+    t.throws(() => {
+      db.pragma(source);
+    }, {
+      instanceOf: TypeError,
+      message: `Invalid pragma: ${source}`
+    });
+  }
+});
+
+test.serial("Verbose option", async (t) => {
+  const statements = [];
+  const [db] = await connect(":memory:", { verbose: (sql) => statements.push(sql) });
+  db.exec("CREATE TABLE t(x)");
+  db.prepare("INSERT INTO t VALUES (?)").run("hello");
+  db.prepare("SELECT * FROM t WHERE x = ?").get("a'b");
+  await new Promise((resolve) => setTimeout(resolve, 50));
+  t.deepEqual(statements.slice(-2), [
+    "INSERT INTO t VALUES ('hello')",
+    "SELECT * FROM t WHERE x = 'a''b'",
+  ]);
+  db.close();
+});
+
+test.serial("Database.trace()", async (t) => {
+  if (t.context.provider === 'sqlite') {
+    // better-sqlite3 has no trace hook
+    t.assert(true);
+    return;
+  }
+  const db = t.context.db;
+  const events = [];
+  db.trace((event) => events.push(event), { row: true });
+  db.prepare("SELECT name FROM users WHERE name != ?").all("Carol");
+  await new Promise((resolve) => setTimeout(resolve, 50));
+  t.deepEqual(events.map((event) => event.type), ["statement", "row", "row", "profile"]);
+  t.is(events[0].sql, "SELECT name FROM users WHERE name != ?");
+  t.is(events[0].expandedSql, "SELECT name FROM users WHERE name != 'Carol'");
+  t.true(events[3].duration >= 0);
+
+  db.trace(null);
+  events.length = 0;
+  db.prepare("SELECT 1").get();
+  await new Promise((resolve) => setTimeout(resolve, 50));
+  t.deepEqual(events, []);
+});
+
+test.serial("Database.function()", async (t) => {
+  const db = t.context.db;
+  db.function("add2", (a, b) => a + b);
+  db.function("concat_all", { varargs: true, deterministic: true }, (...args) => args.join("-"));
+  t.deepEqual(db.prepare("SELECT add2(1, 2)").raw().get(), [3]);
+  t.deepEqual(db.prepare("SELECT concat_all('a', 'b', 'c')").raw().get(), ["a-b-c"]);
+  t.deepEqual(db.prepare("SELECT add2(id, 10) FROM users").pluck().all(), [11, 12]);
+});
+
+test.serial("Database.function() [safe integers]", async (t) => {
+  const db = t.context.db;
+  db.function("type_of", { safeIntegers: true }, (x) => typeof x);
+  t.deepEqual(db.prepare("SELECT type_of(1)").raw().get(), ["bigint"]);
+});
+
+test.serial("Database.function() [error]", async (t) => {
+  const db = t.context.db;
+  const boom = new Error("boom");
+  db.function("fail", () => {
+    throw boom;
+  });
+  t.throws(() => {
+    db.prepare("SELECT fail()").get();
+  }, {
+    is: boom,
+  });
+});
+
+test.serial("Database.aggregate()", async (t) => {
+  const db = t.context.db;
+  db.aggregate("sum_ids", {
+    start: 0,
+    step: (total, id) => total + id,
+  });
+  db.aggregate("join_names", {
+    start: () => [],
+    step: (names, name) => {
+      names.push(name);
+    },
+    result: (names) => names.join(";"),
+  });
+  t.deepEqual(db.prepare("SELECT sum_ids(id), join_names(name) FROM users").raw().get(), [3, "Alice;Bob"]);
+  t.deepEqual(db.prepare("SELECT sum_ids(id), join_names(name) FROM users WHERE id = 0").raw().get(), [0, ""]);
+});
+
+test.serial("Database.aggregate() [window]", async (t) => {
+  const db = t.context.db;
+  db.aggregate("running_sum", {
+    start: 0,
+    step: (total, id) => total + id,
+    inverse: (total, id) => total - id,
+  });
+  const stmt = db.prepare("SELECT running_sum(id) OVER (ORDER BY id ROWS BETWEEN 1 PRECEDING AND CURRENT ROW) FROM users");
+  t.deepEqual(stmt.raw().all(), [[1], [3]]);
+});
+
+test.serial("Database.aggregate() [error]", async (t) => {
+  const db = t.context.db;
+  const boom = new Error("boom");
+  db.aggregate("fail", {
+    step: (total, id) => {
+      throw boom;
+    },
+  });
+  t.throws(() => {
+    db.prepare("SELECT fail(id) FROM users").get();
+  }, {
+    is: boom,
+  });
+});
+
+test.serial("Database.table()", async (t) => {
+  const db = t.context.db;
+  db.table("split", {
+    columns: ["part"],
+    rows: function* (str, sep) {
+      for (const part of str.split(sep)) yield [part];
+    },
+  });
+  db.table("domains", {
+    columns: ["domain", "trusted"],
+    rows: function* () {
+      yield { domain: "example.org", trusted: 1 };
+      yield { domain: "example.com", trusted: 0 };
+    },
+  });
+  t.deepEqual(db.prepare("SELECT part FROM split('a,b,c', ',')").pluck().all(), ["a", "b", "c"]);
+  const stmt = db.prepare(`
+    SELECT users.name, domains.trusted FROM users
+    JOIN domains ON users.email LIKE '%@' || domains.domain
+    ORDER BY users.id
+  `);
+  t.deepEqual(stmt.raw().all(), [["Alice", 1], ["Bob", 0]]);
+});
+
+test.serial("Database.table() [error]", async (t) => {
+  const db = t.context.db;
+  const boom = new Error("boom");
+  db.table("fail", {
+    columns: ["value"],
+    rows: function* () {
+      yield [1];
+      throw boom;
+    },
+  });
+  t.throws(() => {
+    db.prepare("SELECT value FROM fail").all();
+  }, {
+    is: boom,
+  });
+});
+
+test.serial("Database.table() [factory]", async (t) => {
+  const db = t.context.db;
+  db.table("sequence", (count) => ({
+    columns: ["value"],
+    rows: function* () {
+      for (let i = 0; i < Number(count); i++) yield [i];
+    },
+  }));
+  db.exec("CREATE VIRTUAL TABLE three USING sequence(3)");
+  t.deepEqual(db.prepare("SELECT value FROM three").pluck().all(), [0, 1, 2]);
+});
+
+test.serial("Database.table() [invalid row]", async (t) => {
+  const db = t.context.db;
+  db.table("invalid", {
+    columns: ["value"],
+    rows: function* () {
+      yield 42;
+    },
+  });
+  t.throws(() => {
+    db.prepare("SELECT * FROM invalid").all();
+  }, {
+    instanceOf: TypeError,
+    message: 'Virtual table module "invalid" yielded something that isn\'t a valid row object'
+  });
+});
+
+test.serial("Database.backup()", async (t) => {
+  const db = t.context.db;
+  const path = genDatabaseFilename();
+  const progress = [];
+  const result = await db.backup(path, {
+    progress: (info) => {
+      progress.push(info);
+      return 1;
+    },
+  });
+  t.is(result.remainingPages, 0);
+  t.true(result.totalPages > 0);
+  t.is(progress.length, result.totalPages);
+  const [backup] = await connect(path);
+  t.deepEqual(backup.prepare("SELECT name FROM users ORDER BY id").all(), [
+    { name: "Alice" },
+    { name: "Bob" },
+  ]);
+  backup.close();
+  fs.unlinkSync(path);
+});
+
+test.serial("Database.backup() [invalid attached]", async (t) => {
+  const db = t.context.db;
+  const path = genDatabaseFilename();
+  await t.throwsAsync(db.backup(path, { attached: "missing" }), {
+    instanceOf: t.context.errorType,
+    code: "SQLITE_ERROR",
+  });
+  t.false(fs.existsSync(path));
+});
+
+test.serial("Database.serialize()", async (t) => {
+  const db = t.context.db;
+  const buffer = db.serialize();
+  t.true(Buffer.isBuffer(buffer));
+  const [copy] = await connect(buffer);
+  t.is(copy.memory, true);
+  t.deepEqual(copy.prepare("SELECT name FROM users ORDER BY id").all(), [
+    { name: "Alice" },
+    { name: "Bob" },
+  ]);
+  copy.exec("DELETE FROM users");
+  t.is(db.prepare("SELECT count(*) AS count FROM users").get().count, 2);
+  copy.close();
+});
+
+test.serial("Database.serialize() [readonly]", async (t) => {
+  const db = t.context.db;
+  const [copy] = await connect(db.serialize(), { readonly: true });
+  t.throws(() => {
+    copy.exec("DELETE FROM users");
+  }, {
+    instanceOf: t.context.errorType,
+    code: "SQLITE_READONLY",
+  });
+  copy.close();
+});
+
+test.serial("Database.interrupt()", async (t) => {
+  const db = t.context.db;
+  db.function("interrupt_query", () => {
+    db.interrupt();
+    return 1;
+  });
+  const stmt = db.prepare(
+    "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c) SELECT interrupt_query() FROM c"
+  );
+  t.throws(() => {
+    stmt.all();
+  }, {
+    instanceOf: t.context.errorType,
+    message: "interrupted",
+    code: "SQLITE_INTERRUPT",
+  });
+  t.is(db.prepare("SELECT count(*) AS count FROM users").get().count, 2);
+});
+
+test.serial("Statement.interrupt()", async (t) => {
+  if (t.context.provider === 'sqlite') {
+    // skip this test for sqlite
+    t.assert(true);
+    return;
+  }
+  const db = t.context.db;
+  let stmt;
+  db.function("interrupt_query", () => {
+    stmt.interrupt();
+    return 1;
+  });
+  stmt = db.prepare(
+    "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c) SELECT interrupt_query() FROM c"
+  );
+  t.throws(() => {
+    for (const row of stmt.iterate()) {
+    }
+  }, {
+    instanceOf: t.context.errorType,
+    code: "SQLITE_INTERRUPT",
+  });
+});
+
+test.serial("Database.sync() [not a replica]", async (t) => {
+  if (t.context.provider === 'sqlite') {
+    // skip this test for sqlite
+    t.assert(true);
+    return;
+  }
+  const db = t.context.db;
+  t.throws(() => {
+    db.sync();
+  }, {
+    instanceOf: t.context.errorType,
+    code: "LIBSQL_SYNC_ERROR",
+  });
+});
+
+test.serial("Database [unreachable remote]", async (t) => {
+  if (t.context.provider === 'sqlite') {
+    // skip this test for sqlite
+    t.assert(true);
+    return;
+  }
+  const [db] = await connect("http://127.0.0.1:9");
+  t.throws(() => {
+    db.prepare("SELECT 1");
+  }, {
+    instanceOf: t.context.errorType,
+    code: "LIBSQL_HRANA_ERROR",
+  });
+  t.throws(() => {
+    db.exec("SELECT 1");
+  }, {
+    instanceOf: t.context.errorType,
+    code: "LIBSQL_HRANA_ERROR",
+  });
+});
+
+test.serial("Database [remote options]", async (t) => {
+  if (t.context.provider === 'sqlite') {
+    // skip this test for sqlite
+    t.assert(true);
+    return;
+  }
+  const options = [
+    ["timeout", { timeout: 1000 }],
+    ["verbose", { verbose: () => {} }],
+    ["encryptionKey", { encryptionKey: "secret" }],
+    ["encryptionCipher", { encryptionCipher: "aes256cbc" }],
+    ["readonly", { readonly: true }],
+    ["fileMustExist", { fileMustExist: true }],
+    ["flags", { flags: 1 }],
+    ["syncUrl", { syncUrl: "http://127.0.0.1:9" }],
+    ["syncPeriod", { syncPeriod: 60 }],
+  ];
+  for (const [name, option] of options) {
+    await t.throwsAsync(connect("http://127.0.0.1:9", option), {
+      instanceOf: TypeError,
+      message: `The ${name} option is not supported by remote databases`,
+    });
+  }
+});
+
+test.serial("Database.maxWriteReplicationIndex() [not a replica]", async (t) => {
+  if (t.context.provider === 'sqlite') {
+    // skip this test for sqlite
+    t.assert(true);
+    return;
+  }
+  const db = t.context.db;
+  db.exec("INSERT INTO users (name, email) VALUES ('Carol', 'carol@example.net')");
+  t.is(db.maxWriteReplicationIndex(), null);
+});
+
+test.serial("Database.sync() [embedded replica]", async (t) => {
+  const syncUrl = process.env.LIBSQL_SYNC_URL;
+  if (t.context.provider === 'sqlite' || !syncUrl) {
+    // skip this test without a sqld instance to sync from
+    t.assert(true);
+    return;
+  }
+  const path = genDatabaseFilename();
+  const [replica] = await connect(path, { syncUrl });
+  const result = replica.sync();
+  t.is(typeof result.frames_synced, "number");
+  t.is(replica.syncUntil(result.frame_no ?? 0).frame_no, result.frame_no);
+  replica.exec("CREATE TABLE IF NOT EXISTS replicated (x)");
+  replica.exec("INSERT INTO replicated VALUES (1)");
+  const index = replica.maxWriteReplicationIndex();
+  t.is(typeof index, "number");
+  t.true(replica.syncUntil(index).frame_no >= index);
+  replica.close();
+  fs.rmSync(path, { force: true });
+  fs.rmSync(`${path}-info`, { force: true });
+});
+
 test.serial("errors", async (t) => {
   const db = t.context.db;
 
@@ -449,7 +1146,7 @@ const connect = async (path_opt, options = {}) => {
   if (provider === "libsql") {
     const database = process.env.LIBSQL_DATABASE ?? path;
     const x = await import("libsql");
-    const db = new x.default(database, options);
+    const db = new x.default(database, { authToken: process.env.LIBSQL_AUTH_TOKEN, ...options });
     return [db, x.SqliteError, provider];
   }
   if (provider == "sqlite") {
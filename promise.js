@@ -0,0 +1,146 @@
+"use strict";
+
+const Database = require("./wrapper.js");
+
+/**
+ * Database represents a connection whose statements are prepared and
+ * executed off the main thread, returning promises.
+ *
+ * Queries running off the main thread cannot call back into JavaScript, so
+ * statements using functions registered with `function()` or `aggregate()`,
+ * or virtual tables registered with `table()`, reject with a `SqliteError`.
+ */
+class AsyncDatabase extends Database {
+  /**
+   * Syncs an embedded replica with its primary database.
+   */
+  async sync() {
+    return this.db.syncAsync();
+  }
+
+  /**
+   * Syncs an embedded replica until it reaches the given replication index.
+   *
+   * @param {number} replicationIndex - The replication index to wait for.
+   */
+  async syncUntil(replicationIndex) {
+    if (typeof replicationIndex !== 'number') throw new TypeError('Expected first argument to be a number');
+    return this.db.syncUntilAsync(replicationIndex);
+  }
+
+  /**
+   * Prepares a SQL statement for execution.
+   *
+   * @param {string} sql - The SQL statement string to prepare.
+   */
+  async prepare(sql) {
+    return new AsyncStatement(await this.db.prepareAsync(sql));
+  }
+
+  /**
+   * Returns a function that executes the given function in a transaction,
+   * returning a promise of its result.
+   *
+   * @param {function} fn - The function to wrap in a transaction.
+   */
+  transaction(fn) {
+    if (typeof fn !== "function")
+      throw new TypeError("Expected first argument to be a function");
+
+    const db = this;
+    const wrapTxn = (mode) => {
+      return async (...bindParameters) => {
+        await db.exec("BEGIN " + mode);
+        try {
+          const result = await fn(...bindParameters);
+          await db.exec("COMMIT");
+          return result;
+        } catch (err) {
+          await db.exec("ROLLBACK");
+          throw err;
+        }
+      };
+    };
+    const properties = {
+      default: { value: wrapTxn("") },
+      deferred: { value: wrapTxn("DEFERRED") },
+      immediate: { value: wrapTxn("IMMEDIATE") },
+      exclusive: { value: wrapTxn("EXCLUSIVE") },
+      database: { value: this, enumerable: true },
+    };
+    Object.defineProperties(properties.default.value, properties);
+    Object.defineProperties(properties.deferred.value, properties);
+    Object.defineProperties(properties.immediate.value, properties);
+    Object.defineProperties(properties.exclusive.value, properties);
+    return properties.default.value;
+  }
+
+  /**
+   * Executes a pragma and returns its rows.
+   *
+   * @param {string} source - The pragma, optionally schema-qualified and followed by an argument, such as "main.journal_mode = WAL".
+   * @param {object} [options] - The options for the pragma.
+   * @param {boolean} [options.simple] - Returns only the first value of the first row.
+   */
+  async pragma(source, options) {
+    if (options == null) options = {};
+    if (typeof source !== 'string') throw new TypeError('Expected first argument to be a string');
+    if (typeof options !== 'object') throw new TypeError('Expected second argument to be an options object');
+    return this.db.pragmaAsync(source, Boolean(options.simple));
+  }
+
+  /**
+   * Executes a SQL statement.
+   *
+   * @param {string} sql - The SQL statement string to execute.
+   */
+  async exec(sql) {
+    await this.db.execAsync(sql);
+  }
+}
+
+/**
+ * Statement represents a prepared SQL statement whose executions return promises.
+ */
+class AsyncStatement extends Database.Statement {
+  /**
+   * Executes the SQL statement and returns an info object, which includes
+   * the returned rows for statements that return data.
+   *
+   * @param bindParameters - The bind parameters for executing the statement.
+   */
+  async run(...bindParameters) {
+    return this.stmt.runAsync(...bindParameters);
+  }
+
+  /**
+   * Executes the SQL statement and returns the first row.
+   *
+   * @param bindParameters - The bind parameters for executing the statement.
+   */
+  async get(...bindParameters) {
+    return this.stmt.getAsync(...bindParameters);
+  }
+
+  /**
+   * Executes the SQL statement and returns an iterator to the resulting rows.
+   *
+   * @param bindParameters - The bind parameters for executing the statement.
+   */
+  async iterate(...bindParameters) {
+    return super.iterate(...bindParameters);
+  }
+
+  /**
+   * Executes the SQL statement and returns an array of the resulting rows.
+   *
+   * @param bindParameters - The bind parameters for executing the statement.
+   */
+  async all(...bindParameters) {
+    return this.stmt.allAsync(...bindParameters);
+  }
+}
+
+module.exports = AsyncDatabase;
+module.exports.SqliteError = Database.SqliteError;
+module.exports.OpenFlags = Database.OpenFlags;
@@ -1,21 +1,9 @@
 'use strict';
-const descriptor = { value: 'SqliteError', writable: true, enumerable: false, configurable: true };
+const { SqliteError } = require('./index.js');
 
-function SqliteError(message, code, rawCode) {
-        if (new.target !== SqliteError) {
-                return new SqliteError(message, code);
-        }
-        if (typeof code !== 'string') {
-                throw new TypeError('Expected second argument to be a string');
-        }
-        Error.call(this, message);
-        descriptor.value = '' + message;
-        Object.defineProperty(this, 'message', descriptor);
-        Error.captureStackTrace(this, SqliteError);
-        this.code = code;
-        this.rawCode = rawCode
-}
+// SQLite errors are thrown by the native module, which creates them as
+// regular errors with the prototype of this class.
 Object.setPrototypeOf(SqliteError, Error);
 Object.setPrototypeOf(SqliteError.prototype, Error.prototype);
-Object.defineProperty(SqliteError.prototype, 'name', descriptor);
+Object.defineProperty(SqliteError.prototype, 'name', { value: 'SqliteError', writable: true, enumerable: false, configurable: true });
 module.exports = SqliteError;
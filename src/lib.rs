@@ -5,49 +5,255 @@
 #[macro_use]
 extern crate napi_derive;
 
-use napi::bindgen_prelude::{Array, Buffer, FromNapiValue, JsFunction};
-use napi::threadsafe_function::ErrorStrategy::CalleeHandled;
+use libsql::ffi;
+use napi::bindgen_prelude::{Array, Buffer, Either, FromNapiValue, JsFunction, ToNapiValue};
 use napi::threadsafe_function::ThreadsafeFunctionCallMode;
-use napi::threadsafe_function::{ThreadSafeCallContext, ThreadsafeFunction};
-use napi::{Env, JsUnknown, Result, ValueType};
+use napi::threadsafe_function::{ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction};
+use napi::{Env, JsUnknown, NapiRaw, NapiValue, Property, PropertyAttributes, Result, ValueType};
 use once_cell::sync::OnceCell;
+use std::ffi::{c_char, c_int, c_void};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Once;
 use std::time::Duration;
-use std::{cell::RefCell, sync::Arc};
+use std::{
+    cell::{Cell, RefCell},
+    sync::Arc,
+};
 use tokio::{runtime::Runtime, sync::Mutex};
-use tokio::sync::oneshot;
+/// The error thrown for failed SQLite and libSQL operations.
 #[napi]
 pub struct SqliteError {
     #[napi]
     pub message: String,
     #[napi]
     pub code: String,
+    /// The SQLite result code, as reported by libSQL.
     #[napi(js_name = rawCode)]
     pub raw_code: i32,
+    /// The extended SQLite result code.
+    #[napi(js_name = extendedCode)]
+    pub extended_code: i32,
+    /// The byte offset of the token that caused the error in the SQL being
+    /// prepared, if any.
+    #[napi]
+    pub offset: Option<i32>,
+}
+
+#[napi]
+impl SqliteError {
+    #[napi(constructor)]
+    pub fn new(message: String, code: String, raw_code: Option<i32>) -> Self {
+        let raw_code = raw_code.unwrap_or(ffi::SQLITE_ERROR);
+        SqliteError {
+            message,
+            code,
+            raw_code,
+            extended_code: raw_code,
+            offset: None,
+        }
+    }
+
+    fn from_code(message: String, code: &str, raw_code: i32) -> Self {
+        SqliteError::new(message, code.to_string(), Some(raw_code))
+    }
+
+    /// Creates the JS error to throw, which is an `Error` for its stack trace
+    /// whose prototype is changed to `SqliteError.prototype`.
+    fn into_js(self, env: Env) -> Result<JsUnknown> {
+        let mut error = env.create_error(napi::Error::from_reason(self.message))?;
+        let ctor = napi::bindgen_prelude::get_class_constructor("SqliteError\0")
+            .ok_or_else(|| napi::Error::from_reason("SqliteError is not registered"))?;
+        let ctor: JsFunction = unsafe {
+            let mut value = std::ptr::null_mut();
+            napi::check_status!(napi::sys::napi_get_reference_value(
+                env.raw(),
+                ctor,
+                &mut value
+            ))?;
+            JsFunction::from_raw_unchecked(env.raw(), value)
+        };
+        let prototype = ctor
+            .coerce_to_object()?
+            .get_named_property::<napi::JsObject>("prototype")?;
+        let global = env.get_global()?;
+        let object = global.get_named_property_unchecked::<napi::JsObject>("Object")?;
+        let set_prototype_of = object.get_named_property::<JsFunction>("setPrototypeOf")?;
+        set_prototype_of.call(None, &[&error, &prototype])?;
+        let offset = match self.offset {
+            Some(offset) => env.create_int32(offset)?.into_unknown(),
+            None => env.get_null()?.into_unknown(),
+        };
+        let attributes = PropertyAttributes::Writable
+            | PropertyAttributes::Enumerable
+            | PropertyAttributes::Configurable;
+        error.define_properties(&[
+            Property::new("code")?
+                .with_value(&env.create_string(&self.code)?)
+                .with_property_attributes(attributes),
+            Property::new("rawCode")?
+                .with_value(&env.create_int32(self.raw_code)?)
+                .with_property_attributes(attributes),
+            Property::new("extendedCode")?
+                .with_value(&env.create_int32(self.extended_code)?)
+                .with_property_attributes(attributes),
+            Property::new("offset")?
+                .with_value(&offset)
+                .with_property_attributes(attributes),
+        ])?;
+        // Recapture the stack trace, whose header now names `SqliteError`.
+        let error_ctor = global.get_named_property_unchecked::<napi::JsObject>("Error")?;
+        let capture_stack_trace =
+            error_ctor.get_named_property::<JsFunction>("captureStackTrace")?;
+        capture_stack_trace.call(None, &[&error])?;
+        Ok(error.into_unknown())
+    }
+
+    /// Names the authorizer policy in the message of an `SQLITE_AUTH` error,
+    /// which SQLite reports the same way whatever denied the statement.
+    fn denied_by(mut self, policy: bool) -> Self {
+        if policy && self.raw_code == ffi::SQLITE_AUTH {
+            self.message = "Authorization denied by authorizer policy".to_string();
+        }
+        self
+    }
+
+    /// Converts the error to the exception thrown to JS. A JS callback that
+    /// threw while SQLite ran left its exception pending, which is thrown
+    /// instead.
+    fn throw(self, env: &Env) -> napi::Error {
+        let mut pending = false;
+        unsafe { napi::sys::napi_is_exception_pending(env.raw(), &mut pending) };
+        if pending {
+            return napi::Error::new(napi::Status::PendingException, self.message);
+        }
+        match self.into_js(*env) {
+            Ok(error) => napi::Error::from(error),
+            Err(err) => err,
+        }
+    }
 }
 
 struct Error(libsql::Error);
 
-impl From<Error> for napi::Error {
-    fn from(error: Error) -> Self {
+impl Error {
+    /// Converts the error, along with the offset in the SQL of the error last
+    /// reported on the connection, which prepared statements set.
+    fn with_offset(self, handle: *mut ffi::sqlite3) -> SqliteError {
+        let mut error = self.into_sqlite_error();
+        if !handle.is_null() {
+            let offset = unsafe { ffi::sqlite3_error_offset(handle) };
+            error.offset = (offset >= 0).then_some(offset);
+        }
+        error
+    }
+
+    /// Converts the error to the `SqliteError` thrown to JS.
+    fn throw(self, env: &Env) -> napi::Error {
+        self.into_sqlite_error().throw(env)
+    }
+
+    fn into_sqlite_error(self) -> SqliteError {
         use libsql::Error as E;
-        match &error.0 {
+        match self.0 {
             E::SqliteFailure(raw_code, msg) => {
-                let code = map_sqlite_code(*raw_code);
-                if *raw_code == libsql::ffi::SQLITE_AUTH {
-                    throw_sqlite_error(
+                let code = map_sqlite_code(raw_code);
+                if raw_code == libsql::ffi::SQLITE_AUTH {
+                    SqliteError::from_code(
                         "Authorization denied by JS authorizer".to_string(),
-                        code,
-                        *raw_code,
+                        &code,
+                        raw_code,
                     )
                 } else {
-                    throw_sqlite_error(msg.clone(), code, *raw_code)
+                    SqliteError::from_code(msg, &code, raw_code)
                 }
             }
-            _ => todo!(),
+            E::RemoteSqliteFailure(_, extended_code, msg) => {
+                let code = map_sqlite_code(extended_code);
+                SqliteError::from_code(msg, &code, extended_code)
+            }
+            E::Hrana(err) => SqliteError::from_code(
+                err.to_string(),
+                "LIBSQL_HRANA_ERROR",
+                libsql::ffi::SQLITE_ERROR,
+            ),
+            err @ (E::SyncNotSupported(_) | E::Replication(_) | E::Sync(_)) => {
+                SqliteError::from_code(
+                    err.to_string(),
+                    "LIBSQL_SYNC_ERROR",
+                    libsql::ffi::SQLITE_ERROR,
+                )
+            }
+            E::ConnectionFailed(msg) => SqliteError::from_code(
+                msg,
+                "LIBSQL_CONNECTION_FAILED",
+                libsql::ffi::SQLITE_CANTOPEN,
+            ),
+            err @ E::InvalidTlsConfiguration(_) => SqliteError::from_code(
+                err.to_string(),
+                "LIBSQL_CONNECTION_FAILED",
+                libsql::ffi::SQLITE_CANTOPEN,
+            ),
+            err @ (E::WriteDelegation(_) | E::Bincode(_) | E::WalConflict) => {
+                SqliteError::from_code(
+                    err.to_string(),
+                    "LIBSQL_SYNC_ERROR",
+                    libsql::ffi::SQLITE_ERROR,
+                )
+            }
+            E::Misuse(msg) | E::InvalidParserState(msg) => {
+                SqliteError::from_code(msg, "LIBSQL_MISUSE", libsql::ffi::SQLITE_MISUSE)
+            }
+            err @ (E::ExecuteReturnedRows | E::QueryReturnedNoRows | E::InvalidUTF8Path) => {
+                SqliteError::from_code(err.to_string(), "LIBSQL_MISUSE", libsql::ffi::SQLITE_MISUSE)
+            }
+            err @ (E::InvalidColumnName(_) | E::ColumnNotFound(_) | E::InvalidColumnIndex) => {
+                SqliteError::from_code(
+                    err.to_string(),
+                    "LIBSQL_INVALID_COLUMN",
+                    libsql::ffi::SQLITE_RANGE,
+                )
+            }
+            err @ (E::NullValue
+            | E::InvalidColumnType
+            | E::InvalidBlobSize(_)
+            | E::ToSqlConversionFailure(_)) => SqliteError::from_code(
+                err.to_string(),
+                "LIBSQL_CONVERSION_FAILURE",
+                libsql::ffi::SQLITE_MISMATCH,
+            ),
+            err @ (E::Sqlite3SyntaxError(..) | E::Sqlite3ParserError(_)) => SqliteError::from_code(
+                err.to_string(),
+                "LIBSQL_PARSE_ERROR",
+                libsql::ffi::SQLITE_ERROR,
+            ),
+            err @ (E::LoadExtensionNotSupported
+            | E::AuthorizerNotSupported
+            | E::UpdateHookNotSupported
+            | E::FreezeNotSupported(_)
+            | E::ReservedBytesNotSupported
+            | E::Sqlite3UnsupportedStatement) => SqliteError::from_code(
+                err.to_string(),
+                "LIBSQL_NOT_SUPPORTED",
+                libsql::ffi::SQLITE_ERROR,
+            ),
+            E::TransactionalBatchError(msg) => {
+                SqliteError::from_code(msg, "LIBSQL_TRANSACTION_ERROR", libsql::ffi::SQLITE_ERROR)
+            }
+            // `libsql::Error` is non-exhaustive, so variants added by newer
+            // versions still map to an error instead of aborting.
+            err => {
+                SqliteError::from_code(err.to_string(), "LIBSQL_ERROR", libsql::ffi::SQLITE_ERROR)
+            }
         }
     }
 }
 
+impl From<Error> for SqliteError {
+    fn from(error: Error) -> Self {
+        error.into_sqlite_error()
+    }
+}
+
 fn map_sqlite_code(code: i32) -> String {
     match code {
         libsql::ffi::SQLITE_OK => "SQLITE_OK".to_owned(),
@@ -141,119 +347,383 @@ fn map_sqlite_code(code: i32) -> String {
     }
 }
 
-pub fn throw_sqlite_error(message: String, code: String, raw_code: i32) -> napi::Error {
-    let err_json = serde_json::json!({
-        "message": message,
-        "libsqlError": true,
-        "code": code,
-        "rawCode": raw_code
-    });
-    napi::Error::from_reason(err_json.to_string())
-}
-
 impl From<libsql::Error> for Error {
     fn from(error: libsql::Error) -> Self {
         Error(error)
     }
 }
 
-#[napi]
-struct AuthorizerArgs;
+/// An authorization request passed to the hook of `Database.authorizer()`.
+#[napi(object)]
+pub struct AuthorizerArgs {
+    /// The name of the SQLite action code, such as `SQLITE_READ`.
+    pub action: String,
+    /// The table being accessed, if the action concerns a table.
+    pub table: Option<String>,
+    /// The column being read or updated, for `SQLITE_READ` and `SQLITE_UPDATE`.
+    pub column: Option<String>,
+    /// The name of the database being accessed, such as `main` or `temp`.
+    pub database: Option<String>,
+    /// The innermost trigger or view responsible for the access, if any.
+    pub accessor: Option<String>,
+}
+
+/// A rule of an `AuthorizerPolicy`, which matches the authorization requests
+/// whose given fields are all equal to those of the rule.
+#[napi(object)]
+pub struct AuthorizerRule {
+    /// The name of the SQLite action code, such as `SQLITE_READ`.
+    pub action: Option<String>,
+    pub table: Option<String>,
+    pub column: Option<String>,
+    pub database: Option<String>,
+}
+
+/// A declarative authorizer, evaluated without calling into JS. Requests
+/// matching a `deny` rule are denied, then those matching an `ignore` rule
+/// are ignored, then those matching an `allow` rule are allowed, and the
+/// remaining ones get the `default` result, which defaults to `"allow"`.
+#[napi(object)]
+pub struct AuthorizerPolicy {
+    pub allow: Option<Vec<AuthorizerRule>>,
+    pub deny: Option<Vec<AuthorizerRule>>,
+    pub ignore: Option<Vec<AuthorizerRule>>,
+    pub default: Option<String>,
+}
 
 #[napi]
 pub struct Database {
     path: String,
-    db: libsql::Database,
+    db: Arc<libsql::Database>,
     conn: Option<Arc<tokio::sync::Mutex<libsql::Connection>>>,
+    handle: *mut ffi::sqlite3,
     default_safe_integers: RefCell<bool>,
     memory: bool,
+    tasks: Arc<TaskQueue>,
+    authorizer: RefCell<Option<Box<AuthorizerContext>>>,
+    trace: RefCell<Option<Box<TraceContext>>>,
+    readonly: bool,
 }
 
 #[napi(object)]
 pub struct Options {
     pub timeout: Option<f64>,
+    pub authToken: Option<String>,
+    pub syncUrl: Option<String>,
+    /// The interval between periodic syncs of an embedded replica, in seconds.
+    pub syncPeriod: Option<f64>,
+    pub encryptionKey: Option<Either<String, Buffer>>,
+    /// The cipher used with `encryptionKey`, which defaults to `aes256cbc`.
+    pub encryptionCipher: Option<String>,
+    /// Opens a local database without write access.
+    pub readonly: Option<bool>,
+    /// Fails with `SQLITE_CANTOPEN` instead of creating a missing database.
+    pub fileMustExist: Option<bool>,
+    /// The `OpenFlags` of a local database, which replace those derived from
+    /// `readonly` and `fileMustExist`. The threading and cache modes of the
+    /// connection cannot be set.
+    pub flags: Option<i32>,
+    /// Called asynchronously with the SQL of each statement run on a local
+    /// database, with its parameters expanded.
+    #[napi(ts_type = "(sql: string) => void")]
+    pub verbose: Option<JsFunction>,
+}
+
+/// Flags for opening a local database, combined in the `flags` option.
+#[napi]
+pub enum OpenFlags {
+    ReadOnly = 0x0000_0001,
+    ReadWrite = 0x0000_0002,
+    Create = 0x0000_0004,
+    Uri = 0x0000_0040,
+    Memory = 0x0000_0080,
+    NoFollow = 0x0100_0000,
+}
+
+#[napi(object)]
+pub struct SyncResult {
+    #[napi(js_name = "frames_synced")]
+    pub frames_synced: f64,
+    #[napi(js_name = "frame_no")]
+    pub frame_no: Option<f64>,
+}
+
+impl From<libsql::replication::Replicated> for SyncResult {
+    fn from(replicated: libsql::replication::Replicated) -> Self {
+        SyncResult {
+            frames_synced: replicated.frames_synced() as f64,
+            frame_no: replicated.frame_no().map(|frame_no| frame_no as f64),
+        }
+    }
+}
+
+/// The events reported to the hook of `Database.trace()`, which default to
+/// `statement` and `profile`.
+#[napi(object)]
+pub struct TraceOptions {
+    /// Reports each statement as it starts running.
+    pub statement: Option<bool>,
+    /// Reports each statement as it finishes, along with its duration.
+    pub profile: Option<bool>,
+    /// Reports each row returned by a statement.
+    pub row: Option<bool>,
+}
+
+/// An event reported to the hook of `Database.trace()`.
+#[napi(object)]
+pub struct TraceEvent {
+    /// `statement`, `profile` or `row`.
+    #[napi(js_name = "type")]
+    pub kind: String,
+    /// The SQL of the statement.
+    pub sql: String,
+    /// The SQL of the statement with its parameters expanded, for `statement`
+    /// events.
+    pub expandedSql: Option<String>,
+    /// The duration of the statement in seconds, for `profile` events.
+    pub duration: Option<f64>,
+}
+
+#[napi(object)]
+pub struct FunctionOptions {
+    pub deterministic: Option<bool>,
+    pub varargs: Option<bool>,
+    pub directOnly: Option<bool>,
+    pub safeIntegers: Option<bool>,
 }
 
 impl Drop for Database {
     fn drop(&mut self) {
+        self.clear_hooks();
         self.conn = None;
     }
 }
 
 #[napi]
 impl Database {
-    #[napi]
-    /// Only supports arity-1 (callback style) JS hooks: cb => cb("allow")
-    /// This is required due to napi v2 threading restrictions.
-    pub fn authorizer(&self, env: Env, hook: JsFunction) -> Result<()> {
-        // Create a ThreadsafeFunction for the callback that JS will call with "allow"/"deny"
-        let (cb_sender, cb_receiver) = std::sync::mpsc::channel::<String>();
-        let callback = env.create_function_from_closure("rustCallback", move |ctx| {
-            let arg0: JsUnknown = ctx.get::<JsUnknown>(0)?;
-            let js_str = arg0.coerce_to_string()?;
-            let rust_str = js_str.into_utf8()?.into_owned()?;
-            cb_sender.send(rust_str).ok();
-            ctx.env.get_undefined()
-        })?;
-        // Call the user-provided JS hook with our callback as argument, and synchronously get the result
-        hook.call(None, &[callback])?;
-        let result = cb_receiver.recv_timeout(std::time::Duration::from_secs(2)).unwrap_or("deny".to_string());
-        // Register the libsql authorizer hook, using the cached result
-        if let Some(conn) = &self.conn {
-            let cached_result = result.clone();
-            let fut = {
-                let conn = conn.clone();
-                async move {
-                    let mut conn = conn.lock().await;
-                    use std::sync::Arc;
-                    conn.authorizer(Some(Arc::new(move |_action| {
-                        match cached_result.as_str() {
-                            "allow" => libsql::Authorization::Allow,
-                            "deny" => libsql::Authorization::Deny,
-                            "ignore" => libsql::Authorization::Ignore,
-                            _ => libsql::Authorization::Deny,
-                        }
-                    }))).ok();
-                }
-            };
-            let rt = runtime()?;
-            rt.block_on(fut);
+    /// Sets a JS hook that authorizes each action of the statements being
+    /// prepared, returning `"allow"`, `"deny"` or `"ignore"`. Any other
+    /// result, or a hook that throws, denies the action. Asynchronous
+    /// queries cannot call the hook, so connections running them only
+    /// accept a policy.
+    ///
+    /// A policy authorizes actions natively instead, and `null` removes the
+    /// authorizer.
+    #[napi(
+        ts_args_type = "authorizer: ((args: AuthorizerArgs) => 'allow' | 'deny' | 'ignore') | AuthorizerPolicy | null"
+    )]
+    pub fn authorizer(
+        &self,
+        env: Env,
+        authorizer: Option<Either<JsFunction, AuthorizerPolicy>>,
+    ) -> Result<()> {
+        let handle = self.handle(&env)?;
+        let hook = matches!(authorizer, Some(Either::A(_)));
+        if hook && self.tasks.pending.load(Ordering::SeqCst) > 0 {
+            return Err(throw_async_authorizer_error(&env));
         }
+        let authorizer = match authorizer {
+            Some(Either::A(hook)) => Box::new(AuthorizerContext::Hook {
+                env: env.raw(),
+                hook: env.create_reference(hook)?,
+            }),
+            Some(Either::B(policy)) => Box::new(AuthorizerContext::Policy(policy.try_into()?)),
+            None => {
+                unsafe { ffi::sqlite3_set_authorizer(handle, None, std::ptr::null_mut()) };
+                self.authorizer.replace(None);
+                self.tasks.js_authorizer.store(false, Ordering::SeqCst);
+                return Ok(());
+            }
+        };
+        unsafe {
+            ffi::sqlite3_set_authorizer(
+                handle,
+                Some(call_authorizer),
+                &*authorizer as *const AuthorizerContext as *mut c_void,
+            );
+        }
+        // Replacing the context releases the previous hook, which SQLite no
+        // longer references.
+        self.authorizer.replace(Some(authorizer));
+        self.tasks.js_authorizer.store(hook, Ordering::SeqCst);
         Ok(())
     }
 
+    /// Whether the connection is authorized by an authorizer policy.
+    fn has_policy(&self) -> bool {
+        matches!(
+            self.authorizer.borrow().as_deref(),
+            Some(AuthorizerContext::Policy(_))
+        )
+    }
+
+    /// Sets a JS hook that is called with the statements run on the
+    /// connection, including those of asynchronous queries. Events are
+    /// delivered asynchronously, in order, and `null` removes the hook, as
+    /// well as the `verbose` one.
+    #[napi(ts_args_type = "hook: ((event: TraceEvent) => void) | null, options?: TraceOptions")]
+    pub fn trace(
+        &self,
+        env: Env,
+        hook: Option<JsFunction>,
+        options: Option<TraceOptions>,
+    ) -> Result<()> {
+        self.set_trace(&env, hook, options, false)
+    }
+
+    /// Pulls the frames written to the primary database since the last sync
+    /// into the embedded replica.
+    #[napi]
+    pub fn sync(&self, env: Env) -> Result<SyncResult> {
+        let rt = runtime()?;
+        let replicated = rt
+            .block_on(self.db.sync())
+            .map_err(|e| Error::from(e).throw(&env))?;
+        Ok(replicated.into())
+    }
+
+    /// Syncs the embedded replica until it reaches `replication_index`.
+    #[napi]
+    pub fn syncUntil(&self, env: Env, replication_index: f64) -> Result<SyncResult> {
+        let rt = runtime()?;
+        let replicated = rt
+            .block_on(self.db.sync_until(replication_index as u64))
+            .map_err(|e| Error::from(e).throw(&env))?;
+        Ok(replicated.into())
+    }
+
+    /// Syncs the embedded replica off the JS thread, resolving to the same
+    /// result as `sync()`.
+    #[napi(ts_return_type = "Promise<SyncResult>")]
+    pub fn syncAsync(&self, env: Env) -> Result<napi::JsObject> {
+        let task = SyncTask {
+            db: self.db.clone(),
+            until: None,
+        };
+        self.tasks.submit(&env, task)
+    }
+
+    /// Syncs the embedded replica until it reaches `replication_index` off
+    /// the JS thread, resolving to the same result as `syncUntil()`.
+    #[napi(ts_return_type = "Promise<SyncResult>")]
+    pub fn syncUntilAsync(&self, env: Env, replication_index: f64) -> Result<napi::JsObject> {
+        let task = SyncTask {
+            db: self.db.clone(),
+            until: Some(replication_index as u64),
+        };
+        self.tasks.submit(&env, task)
+    }
+
     #[napi(getter)]
     pub fn memory(&self) -> bool {
         self.memory
     }
+
+    /// The path or URL the database was opened with.
+    #[napi(getter)]
+    pub fn name(&self) -> String {
+        self.path.clone()
+    }
+
+    /// Whether the main database of the connection is read-only.
+    #[napi(getter)]
+    pub fn readonly(&self) -> bool {
+        if self.conn.is_none() || self.handle.is_null() {
+            return self.readonly;
+        }
+        unsafe { ffi::sqlite3_db_readonly(self.handle, c"main".as_ptr()) == 1 }
+    }
     #[napi(constructor)]
-    pub fn new(path: String, opts: Option<Options>) -> Result<Self> {
+    pub fn new(env: Env, path: String, opts: Option<Options>) -> Result<Self> {
         let rt = runtime()?;
         let remote = is_remote_path(&path);
+        if remote {
+            check_remote_options(&env, opts.as_ref())?;
+        } else if opts.as_ref().is_some_and(|opts| opts.syncUrl.is_some()) {
+            check_replica_options(&env, opts.as_ref())?;
+        }
+        let auth_token = opts
+            .as_ref()
+            .and_then(|opts| opts.authToken.clone())
+            .unwrap_or_default();
+        let sync_url = opts.as_ref().and_then(|opts| opts.syncUrl.clone());
+        let encryption = encryption_config(opts.as_ref())?;
+        let encrypted = encryption.is_some();
         let db = if remote {
-            todo!("Remote databases are not supported yet");
+            let builder = libsql::Builder::new_remote(path.clone(), auth_token);
+            rt.block_on(builder.build())
+                .map_err(|e| Error::from(e).throw(&env))?
+        } else if let Some(sync_url) = sync_url {
+            let mut builder = libsql::Builder::new_remote_replica(&path, sync_url, auth_token);
+            let sync_period = opts.as_ref().and_then(|opts| opts.syncPeriod);
+            if let Some(sync_period) = sync_period.filter(|period| *period > 0.0) {
+                builder = builder.sync_interval(Duration::from_secs_f64(sync_period));
+            }
+            if let Some(encryption) = encryption {
+                builder = builder.encryption_config(encryption);
+            }
+            rt.block_on(builder.build())
+                .map_err(|e| Error::from(e).throw(&env))?
         } else {
-            let builder = libsql::Builder::new_local(&path);
-            rt.block_on(builder.build()).map_err(Error::from)?
+            let flags = open_flags(&env, &path, opts.as_ref())?;
+            let mut builder = libsql::Builder::new_local(&path).flags(flags);
+            if let Some(encryption) = encryption {
+                builder = builder.encryption_config(encryption);
+            }
+            rt.block_on(builder.build())
+                .map_err(|e| Error::from(e).throw(&env))?
+        };
+        // Remote connections and replicas spawn their HTTP client on the runtime.
+        let _guard = rt.enter();
+        let (conn, handle) = if remote {
+            let conn = db.connect().map_err(|e| Error::from(e).throw(&env))?;
+            (conn, std::ptr::null_mut())
+        } else {
+            let (conn, handle) = connect(&db).map_err(|e| e.throw(&env))?;
+            // Extensions can only be loaded after an explicit `enableLoadExtension()`.
+            conn.load_extension_disable()
+                .map_err(|e| Error::from(e).throw(&env))?;
+            (conn, handle)
         };
-        let conn = db.connect().map_err(Error::from)?;
+        if encrypted {
+            // A wrong key is only noticed when the first page is decrypted.
+            rt.block_on(conn.execute_batch("SELECT count(*) FROM sqlite_schema"))
+                .map_err(|err| match err {
+                    libsql::Error::SqliteFailure(ffi::SQLITE_NOTADB, _) => {
+                        libsql::Error::SqliteFailure(
+                            ffi::SQLITE_NOTADB,
+                            "file is not a database or the encryption key is wrong".to_string(),
+                        )
+                    }
+                    err => err,
+                })
+                .map_err(|e| Error::from(e).throw(&env))?;
+        }
         let default_safe_integers = RefCell::new(false);
         let memory = path == ":memory:";
-        let timeout = match opts {
-            Some(opts) => opts.timeout.unwrap_or(0.0),
-            None => 0.0,
-        };
+        let readonly =
+            !handle.is_null() && unsafe { ffi::sqlite3_db_readonly(handle, c"main".as_ptr()) == 1 };
+        let timeout = opts.as_ref().and_then(|opts| opts.timeout).unwrap_or(0.0);
         if timeout > 0.0 {
             conn.busy_timeout(Duration::from_millis(timeout as u64))
-                .map_err(Error::from)?
+                .map_err(|e| Error::from(e).throw(&env))?
         }
-        Ok(Database {
+        let database = Database {
             path: path.clone(),
-            db,
+            db: Arc::new(db),
             conn: Some(Arc::new(Mutex::new(conn))),
+            handle,
             default_safe_integers,
             memory,
-        })
+            tasks: Arc::new(TaskQueue::new()?),
+            authorizer: RefCell::new(None),
+            trace: RefCell::new(None),
+            readonly,
+        };
+        if let Some(verbose) = opts.and_then(|opts| opts.verbose) {
+            database.set_trace(&env, Some(verbose), None, true)?;
+        }
+        Ok(database)
     }
 
     #[napi(js_name = "inTransaction")]
@@ -275,63 +745,449 @@ impl Database {
         let rt = runtime()?;
         let conn = match &self.conn {
             Some(conn) => conn.clone(),
-            None => return Err(throw_database_closed_error(&env).into()),
+            None => return Err(throw_database_closed_error(&env)),
         };
         let conn_ = conn.clone();
-        let stmt = rt
-            .block_on(async move {
+        let handle = self.handle;
+        let (stmt, raw_stmt) = rt
+            .block_on(async {
                 let conn = conn_.lock().await;
-                conn.prepare(&sql).await
+                let stmt = conn
+                    .prepare(&sql)
+                    .await
+                    .map_err(|e| Error::from(e).with_offset(handle))?;
+                Ok((stmt, RawStatement::last_prepared(handle, &sql)))
             })
-            .map_err(Error::from)?;
-        Ok(Statement {
-            stmt: Arc::new(Mutex::new(stmt)),
-            conn: conn.clone(),
-            safe_ints: RefCell::new(*self.default_safe_integers.borrow()),
-            raw: RefCell::new(false),
-            pluck: RefCell::new(false),
+            .map_err(|e: SqliteError| e.denied_by(self.has_policy()).throw(&env))?;
+        Ok(Statement::new(
+            stmt,
+            raw_stmt,
+            sql,
+            conn,
+            self.handle,
+            self.tasks.clone(),
+            *self.default_safe_integers.borrow(),
+        ))
+    }
+
+    /// Prepares a statement off the JS thread, resolving to the prepared
+    /// statement.
+    #[napi(ts_return_type = "Promise<Statement>")]
+    pub fn prepareAsync(&self, env: Env, sql: String) -> Result<napi::JsObject> {
+        let conn = match &self.conn {
+            Some(conn) => conn.clone(),
+            None => return Err(throw_database_closed_error(&env)),
+        };
+        self.tasks.submit(
+            &env,
+            PrepareTask {
+                conn,
+                sql,
+                handle: self.handle,
+                tasks: self.tasks.clone(),
+                safe_ints: *self.default_safe_integers.borrow(),
+                policy: self.has_policy(),
+            },
+        )
+    }
+
+    /// Runs a pragma, returning its rows, or the first value of its first row
+    /// in `simple` mode. `source` is the pragma name, optionally qualified by
+    /// a schema name and followed by its argument, such as
+    /// `main.journal_mode = WAL`.
+    #[napi]
+    pub fn pragma(&self, env: Env, source: String, simple: Option<bool>) -> Result<JsUnknown> {
+        let conn = match &self.conn {
+            Some(conn) => conn.clone(),
+            None => return Err(throw_database_closed_error(&env)),
+        };
+        let Some(sql) = pragma_sql(&source) else {
+            return Err(throw_invalid_pragma_error(&env, &source));
+        };
+        let rt = runtime()?;
+        let (columns, rows) = rt
+            .block_on(pragma_rows(&conn, &sql))
+            .map_err(|e| e.throw(&env))?;
+        let safe_ints = *self.default_safe_integers.borrow();
+        pragma_result(&env, columns, rows, safe_ints, simple.unwrap_or(false))
+    }
+
+    /// Runs a pragma off the JS thread, resolving to the same value as
+    /// `pragma()`.
+    #[napi(ts_return_type = "Promise<unknown>")]
+    pub fn pragmaAsync(
+        &self,
+        env: Env,
+        source: String,
+        simple: Option<bool>,
+    ) -> Result<napi::JsObject> {
+        let conn = match &self.conn {
+            Some(conn) => conn.clone(),
+            None => return Err(throw_database_closed_error(&env)),
+        };
+        let Some(sql) = pragma_sql(&source) else {
+            return Err(throw_invalid_pragma_error(&env, &source));
+        };
+        let task = PragmaTask {
+            conn,
+            sql,
+            safe_ints: *self.default_safe_integers.borrow(),
+            simple: simple.unwrap_or(false),
+            policy: self.has_policy(),
+        };
+        self.tasks.submit(&env, task)
+    }
+
+    /// Starts an online backup of the `attached` database into the file at
+    /// `filename`, whose pages are then copied with `Backup.transfer()`.
+    #[napi]
+    pub fn backup(&self, env: Env, attached: String, filename: String) -> Result<Backup> {
+        let handle = self.handle(&env)?;
+        let conn = match &self.conn {
+            Some(conn) => conn.clone(),
+            None => return Err(throw_database_closed_error(&env)),
+        };
+        let attached = std::ffi::CString::new(attached)?;
+        let filename = std::ffi::CString::new(filename)?;
+        let mut dest = std::ptr::null_mut();
+        let rc = unsafe {
+            ffi::sqlite3_open_v2(
+                filename.as_ptr(),
+                &mut dest,
+                ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE,
+                std::ptr::null(),
+            )
+        };
+        if let Err(err) = check_rc(dest, rc) {
+            unsafe { ffi::sqlite3_close(dest) };
+            return Err(err.throw(&env));
+        }
+        let backup =
+            unsafe { ffi::sqlite3_backup_init(dest, c"main".as_ptr(), handle, attached.as_ptr()) };
+        if backup.is_null() {
+            let err = check_rc(dest, unsafe { ffi::sqlite3_errcode(dest) });
+            unsafe { ffi::sqlite3_close(dest) };
+            return Err(err.err().map_or_else(
+                || napi::Error::from_reason("Failed to start the backup"),
+                |err| err.throw(&env),
+            ));
+        }
+        Ok(Backup {
+            backup,
+            dest,
+            conn: Some(conn),
         })
     }
 
+    /// Returns the image of the `attached` database as a buffer.
     #[napi]
-    pub fn pragma(&self) -> Result<()> {
-        // TODO: Implement pragma
-        Ok(())
+    pub fn serialize(&self, env: Env, attached: String) -> Result<Buffer> {
+        let handle = self.handle(&env)?;
+        let schema = std::ffi::CString::new(attached.clone())?;
+        let mut size: ffi::sqlite3_int64 = 0;
+        let data = unsafe { ffi::sqlite3_serialize(handle, schema.as_ptr(), &mut size, 0) };
+        if data.is_null() {
+            return match size {
+                0 => Ok(Vec::new().into()),
+                size if size < 0 => Err(Error(libsql::Error::SqliteFailure(
+                    ffi::SQLITE_ERROR,
+                    format!("unknown database {}", attached),
+                ))
+                .throw(&env)),
+                _ => Err(Error(libsql::Error::SqliteFailure(
+                    ffi::SQLITE_NOMEM,
+                    "out of memory".to_string(),
+                ))
+                .throw(&env)),
+            };
+        }
+        let image = unsafe { std::slice::from_raw_parts(data, size as usize) }.to_vec();
+        unsafe { ffi::sqlite3_free(data as *mut c_void) };
+        Ok(image.into())
     }
 
+    /// Replaces the `attached` database with an in-memory copy of the image in
+    /// `data`, as returned by `serialize()`.
     #[napi]
-    pub fn backup(&self) -> Result<()> {
-        todo!();
+    pub fn deserialize(
+        &self,
+        env: Env,
+        data: Buffer,
+        attached: String,
+        readonly: bool,
+    ) -> Result<()> {
+        let handle = self.handle(&env)?;
+        let schema = std::ffi::CString::new(attached)?;
+        let size = data.len();
+        let image = unsafe { ffi::sqlite3_malloc64(size as u64) } as *mut u8;
+        if image.is_null() && size > 0 {
+            return Err(Error(libsql::Error::SqliteFailure(
+                ffi::SQLITE_NOMEM,
+                "out of memory".to_string(),
+            ))
+            .throw(&env));
+        }
+        if size > 0 {
+            unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), image, size) };
+        }
+        let mut flags = ffi::SQLITE_DESERIALIZE_FREEONCLOSE | ffi::SQLITE_DESERIALIZE_RESIZEABLE;
+        if readonly {
+            flags |= ffi::SQLITE_DESERIALIZE_READONLY;
+        }
+        // SQLite takes ownership of the image, even if this fails.
+        let rc = unsafe {
+            ffi::sqlite3_deserialize(
+                handle,
+                schema.as_ptr(),
+                image,
+                size as ffi::sqlite3_int64,
+                size as ffi::sqlite3_int64,
+                flags as u32,
+            )
+        };
+        check_rc(handle, rc).map_err(|e| e.throw(&env))
     }
 
+    /// Registers a user-defined scalar SQL function backed by a JS callback.
     #[napi]
-    pub fn serialize(&self) -> Result<()> {
-        todo!();
+    pub fn function(
+        &self,
+        env: Env,
+        name: String,
+        options: FunctionOptions,
+        callback: JsFunction,
+    ) -> Result<()> {
+        let handle = self.handle(&env)?;
+        let arg_count = if options.varargs.unwrap_or(false) {
+            -1
+        } else {
+            function_length(&env, &callback)?
+        };
+        let mut flags = ffi::SQLITE_UTF8;
+        if options.deterministic.unwrap_or(false) {
+            flags |= ffi::SQLITE_DETERMINISTIC;
+        }
+        if options.directOnly.unwrap_or(false) {
+            flags |= ffi::SQLITE_DIRECTONLY;
+        }
+        let function = Box::new(FunctionContext {
+            env: env.raw(),
+            callback: env.create_reference(callback)?,
+            safe_ints: options
+                .safeIntegers
+                .unwrap_or(*self.default_safe_integers.borrow()),
+        });
+        let name = std::ffi::CString::new(name)?;
+        let rc = unsafe {
+            ffi::sqlite3_create_function_v2(
+                handle,
+                name.as_ptr(),
+                arg_count,
+                flags,
+                Box::into_raw(function) as *mut c_void,
+                Some(call_function),
+                None,
+                None,
+                Some(destroy_function),
+            )
+        };
+        check_rc(handle, rc).map_err(|e| e.throw(&env))
     }
 
+    /// Registers a user-defined aggregate SQL function backed by JS callbacks.
+    /// Supplying `inverse` makes the aggregate usable as a window function.
     #[napi]
-    pub fn function(&self) -> Result<()> {
-        todo!();
+    #[allow(clippy::too_many_arguments)]
+    pub fn aggregate(
+        &self,
+        env: Env,
+        name: String,
+        options: FunctionOptions,
+        start: JsUnknown,
+        step: JsFunction,
+        inverse: Option<JsFunction>,
+        result: Option<JsFunction>,
+    ) -> Result<()> {
+        let handle = self.handle(&env)?;
+        let arg_count = if options.varargs.unwrap_or(false) {
+            -1
+        } else {
+            let step_count = function_length(&env, &step)?;
+            let inverse_count = match &inverse {
+                Some(inverse) => function_length(&env, inverse)?,
+                None => 0,
+            };
+            (step_count.max(inverse_count) - 1).max(0)
+        };
+        let mut flags = ffi::SQLITE_UTF8;
+        if options.deterministic.unwrap_or(false) {
+            flags |= ffi::SQLITE_DETERMINISTIC;
+        }
+        if options.directOnly.unwrap_or(false) {
+            flags |= ffi::SQLITE_DIRECTONLY;
+        }
+        let window = inverse.is_some();
+        let mut callbacks = env.create_array_with_length(4)?;
+        callbacks.set_element(AGGREGATE_START, start)?;
+        callbacks.set_element(AGGREGATE_STEP, step)?;
+        if let Some(inverse) = inverse {
+            callbacks.set_element(AGGREGATE_INVERSE, inverse)?;
+        }
+        if let Some(result) = result {
+            callbacks.set_element(AGGREGATE_RESULT, result)?;
+        }
+        let aggregate = Box::new(FunctionContext {
+            env: env.raw(),
+            callback: env.create_reference(callbacks)?,
+            safe_ints: options
+                .safeIntegers
+                .unwrap_or(*self.default_safe_integers.borrow()),
+        });
+        let name = std::ffi::CString::new(name)?;
+        let rc = unsafe {
+            ffi::sqlite3_create_window_function(
+                handle,
+                name.as_ptr(),
+                arg_count,
+                flags,
+                Box::into_raw(aggregate) as *mut c_void,
+                Some(aggregate_step),
+                Some(aggregate_final),
+                window.then_some(aggregate_value as unsafe extern "C" fn(_)),
+                window.then_some(aggregate_inverse as unsafe extern "C" fn(_, _, _)),
+                Some(destroy_function),
+            )
+        };
+        check_rc(handle, rc).map_err(|e| e.throw(&env))
     }
 
+    /// Registers a virtual table module backed by a JS factory, which returns
+    /// the table definition for the arguments of each table created with it.
+    /// Eponymous modules are used directly as table-valued functions.
     #[napi]
-    pub fn aggregate(&self) -> Result<()> {
-        todo!();
+    pub fn table(
+        &self,
+        env: Env,
+        name: String,
+        factory: JsFunction,
+        eponymous: bool,
+    ) -> Result<()> {
+        let handle = self.handle(&env)?;
+        let module = Box::new(FunctionContext {
+            env: env.raw(),
+            callback: env.create_reference(factory)?,
+            safe_ints: *self.default_safe_integers.borrow(),
+        });
+        let name = std::ffi::CString::new(name)?;
+        let rc = unsafe {
+            ffi::sqlite3_create_module_v2(
+                handle,
+                name.as_ptr(),
+                if eponymous {
+                    &EPONYMOUS_TABLE_MODULE
+                } else {
+                    &TABLE_MODULE
+                },
+                Box::into_raw(module) as *mut c_void,
+                Some(destroy_function),
+            )
+        };
+        check_rc(handle, rc).map_err(|e| e.throw(&env))
     }
 
+    /// Toggles loading extensions with `loadExtension()`, which is disabled by default.
     #[napi]
-    pub fn table(&self) -> Result<()> {
-        todo!();
+    pub fn enableLoadExtension(&self, env: Env, toggle: Option<bool>) -> Result<()> {
+        let rt = runtime()?;
+        let conn = match &self.conn {
+            Some(conn) => conn.clone(),
+            None => return Err(throw_database_closed_error(&env)),
+        };
+        rt.block_on(async move {
+            let conn = conn.lock().await;
+            if toggle.unwrap_or(true) {
+                conn.load_extension_enable()
+            } else {
+                conn.load_extension_disable()
+            }
+        })
+        .map_err(|e| Error::from(e).throw(&env))?;
+        Ok(())
     }
 
     #[napi]
-    pub fn loadExtension(&self, _path: String) -> Result<()> {
-        todo!();
+    pub fn loadExtension(&self, env: Env, path: String, entry_point: Option<String>) -> Result<()> {
+        let rt = runtime()?;
+        let conn = match &self.conn {
+            Some(conn) => conn.clone(),
+            None => return Err(throw_database_closed_error(&env)),
+        };
+        rt.block_on(async move {
+            let conn = conn.lock().await;
+            conn.load_extension(&path, entry_point.as_deref())
+        })
+        .map_err(|e| Error::from(e).throw(&env))?;
+        Ok(())
     }
 
+    /// Re-encrypts the database with a new key and cipher, which also encrypts
+    /// a plaintext database. An empty or missing key decrypts the database.
     #[napi]
-    pub fn maxWriteReplicationIndex(&self) -> Result<()> {
-        todo!();
+    pub fn rekey(
+        &self,
+        env: Env,
+        key: Option<Either<String, Buffer>>,
+        cipher: Option<String>,
+    ) -> Result<()> {
+        // Only local databases and replicas are encrypted at rest.
+        let handle = self.handle(&env)?;
+        let conn = match &self.conn {
+            Some(conn) => conn.clone(),
+            None => return Err(throw_database_closed_error(&env)),
+        };
+        // The key is handed to the cipher directly, never through SQL text,
+        // which would expose it to the trace hooks.
+        let key = match &key {
+            Some(Either::A(key)) => key.as_bytes().to_vec(),
+            Some(Either::B(key)) => key.to_vec(),
+            None => vec![],
+        };
+        let cipher_id = if key.is_empty() {
+            None
+        } else {
+            let cipher = cipher.unwrap_or_else(|| "aes256cbc".to_string());
+            cipher.parse::<libsql::Cipher>().map_err(|_| {
+                napi::Error::from_reason(format!("Unsupported encryption cipher: {}", cipher))
+            })?;
+            let name = std::ffi::CString::new(cipher).unwrap();
+            Some(unsafe { sqlite3mc_cipher_index(name.as_ptr()) })
+        };
+        let rt = runtime()?;
+        rt.block_on(async move {
+            let _conn = conn.lock().await;
+            if let Some(cipher_id) = cipher_id {
+                let rc = unsafe { sqlite3mc_config(handle, c"default:cipher".as_ptr(), cipher_id) };
+                if rc == -1 {
+                    return Err(Error(libsql::Error::Misuse(
+                        "failed to set the encryption cipher".to_string(),
+                    )));
+                }
+            }
+            let rc =
+                unsafe { sqlite3_rekey(handle, key.as_ptr() as *const c_void, key.len() as c_int) };
+            check_rc(handle, rc)
+        })
+        .map_err(|e| e.throw(&env))?;
+        Ok(())
+    }
+
+    /// Returns the highest replication index of the writes made through this
+    /// database, which other replicas can wait for with `syncUntil()`.
+    #[napi]
+    pub fn maxWriteReplicationIndex(&self) -> Option<f64> {
+        self.db
+            .max_write_replication_index()
+            .map(|index| index as f64)
     }
 
     #[napi]
@@ -339,23 +1195,48 @@ impl Database {
         let rt = runtime()?;
         let conn = match &self.conn {
             Some(conn) => conn.clone(),
-            None => return Err(throw_database_closed_error(&env).into()),
+            None => return Err(throw_database_closed_error(&env)),
         };
         rt.block_on(async move {
             let conn = conn.lock().await;
             conn.execute_batch(&sql).await
         })
-        .map_err(Error::from)?;
+        .map_err(|e| {
+            SqliteError::from(Error::from(e))
+                .denied_by(self.has_policy())
+                .throw(&env)
+        })?;
         Ok(())
     }
 
+    /// Executes SQL statements off the JS thread.
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn execAsync(&self, env: Env, sql: String) -> Result<napi::JsObject> {
+        let conn = match &self.conn {
+            Some(conn) => conn.clone(),
+            None => return Err(throw_database_closed_error(&env)),
+        };
+        let task = ExecTask {
+            conn,
+            sql,
+            policy: self.has_policy(),
+        };
+        self.tasks.submit(&env, task)
+    }
+
+    /// Aborts the queries running on the connection, which fail with
+    /// `SQLITE_INTERRUPT`, along with its queued async tasks.
     #[napi]
-    pub fn interrupt(&self) -> Result<()> {
-        todo!();
+    pub fn interrupt(&self, env: Env) -> Result<()> {
+        match &self.conn {
+            Some(conn) => interrupt(conn, self.handle, &self.tasks).map_err(|e| e.throw(&env)),
+            None => Ok(()),
+        }
     }
 
     #[napi]
     pub fn close(&mut self) -> Result<()> {
+        self.clear_hooks();
         self.conn = None;
         Ok(())
     }
@@ -370,37 +1251,1416 @@ impl Database {
     pub fn unsafeMode(&self) -> Result<()> {
         todo!();
     }
+
+    /// Removes the authorizer and trace hooks, which statements that outlive
+    /// the database would otherwise call.
+    fn clear_hooks(&mut self) {
+        if self.conn.is_some() && !self.handle.is_null() {
+            unsafe {
+                if self.authorizer.get_mut().is_some() {
+                    ffi::sqlite3_set_authorizer(self.handle, None, std::ptr::null_mut());
+                }
+                if self.trace.get_mut().is_some() {
+                    ffi::sqlite3_trace_v2(self.handle, 0, None, std::ptr::null_mut());
+                }
+            }
+        }
+        self.authorizer.replace(None);
+        self.trace.replace(None);
+    }
+
+    /// Sets the hook of `trace()`, or the `verbose` hook, which is called
+    /// with the SQL of statement events only.
+    fn set_trace(
+        &self,
+        env: &Env,
+        hook: Option<JsFunction>,
+        options: Option<TraceOptions>,
+        verbose: bool,
+    ) -> Result<()> {
+        let handle = self.handle(env)?;
+        let Some(hook) = hook else {
+            unsafe { ffi::sqlite3_trace_v2(handle, 0, None, std::ptr::null_mut()) };
+            self.trace.replace(None);
+            return Ok(());
+        };
+        let options = options.as_ref();
+        let enabled = |event: Option<bool>, default: bool| !verbose && event.unwrap_or(default);
+        let mut mask = ffi::SQLITE_TRACE_STMT;
+        if !verbose && options.and_then(|options| options.statement) == Some(false) {
+            mask = 0;
+        }
+        if enabled(options.and_then(|options| options.profile), true) {
+            mask |= ffi::SQLITE_TRACE_PROFILE;
+        }
+        if enabled(options.and_then(|options| options.row), false) {
+            mask |= ffi::SQLITE_TRACE_ROW;
+        }
+        let mut hook: ThreadsafeFunction<TraceEvent, ErrorStrategy::Fatal> = hook
+            .create_threadsafe_function(0, move |ctx: ThreadSafeCallContext<TraceEvent>| {
+                let value = if verbose {
+                    let sql = ctx.value.expandedSql.unwrap_or(ctx.value.sql);
+                    ctx.env.create_string(&sql)?.into_unknown()
+                } else {
+                    unsafe {
+                        JsUnknown::from_raw_unchecked(
+                            ctx.env.raw(),
+                            TraceEvent::to_napi_value(ctx.env.raw(), ctx.value)?,
+                        )
+                    }
+                };
+                Ok(vec![value])
+            })?;
+        // Tracing must not keep the process alive.
+        hook.unref(env)?;
+        let trace = Box::new(TraceContext { hook });
+        unsafe {
+            ffi::sqlite3_trace_v2(
+                handle,
+                mask as u32,
+                Some(call_trace),
+                &*trace as *const TraceContext as *mut c_void,
+            );
+        }
+        self.trace.replace(Some(trace));
+        Ok(())
+    }
+
+    /// Returns the raw SQLite handle of an open, local database connection.
+    fn handle(&self, env: &Env) -> Result<*mut ffi::sqlite3> {
+        if self.conn.is_none() {
+            return Err(throw_database_closed_error(env));
+        }
+        if self.handle.is_null() {
+            return Err(napi::Error::from_reason(
+                "This operation is only supported on local databases",
+            ));
+        }
+        Ok(self.handle)
+    }
 }
 
-fn is_remote_path(path: &str) -> bool {
-    path.starts_with("libsql://") || path.starts_with("http://") || path.starts_with("https://")
+// The SQLite3 Multiple Ciphers API behind libSQL's encryption at rest.
+extern "C" {
+    fn sqlite3mc_cipher_index(cipher: *const c_char) -> c_int;
+    fn sqlite3mc_config(db: *mut ffi::sqlite3, param: *const c_char, value: c_int) -> c_int;
+    fn sqlite3_rekey(db: *mut ffi::sqlite3, key: *const c_void, len: c_int) -> c_int;
+}
+
+thread_local! {
+    static OPENED_HANDLE: Cell<*mut ffi::sqlite3> = const { Cell::new(std::ptr::null_mut()) };
+}
+
+/// SQLite auto-extension that records the handle of the connection being opened.
+unsafe extern "C" fn capture_handle(
+    db: *mut ffi::sqlite3,
+    _err: *mut *const c_char,
+    _api: *const ffi::sqlite3_api_routines,
+) -> c_int {
+    OPENED_HANDLE.with(|handle| handle.set(db));
+    ffi::SQLITE_OK
+}
+
+/// Connects to a local database, returning the connection and its raw SQLite
+/// handle, which libsql does not expose.
+fn connect(
+    db: &libsql::Database,
+) -> std::result::Result<(libsql::Connection, *mut ffi::sqlite3), Error> {
+    static CAPTURE_HANDLE: Once = Once::new();
+    CAPTURE_HANDLE.call_once(|| {
+        // libsql configures the SQLite threading mode when the first local
+        // database is opened, which must happen before registering an
+        // auto-extension initializes the library.
+        libsql::Database::open_in_memory().ok();
+        unsafe { ffi::sqlite3_auto_extension(Some(capture_handle)) };
+    });
+    OPENED_HANDLE.with(|handle| handle.set(std::ptr::null_mut()));
+    let conn = db.connect().map_err(|err| match err {
+        // libsql reports failures to open the file with only the result code
+        // at the end of the message.
+        libsql::Error::ConnectionFailed(msg) => {
+            let code = msg
+                .rsplit(' ')
+                .next()
+                .and_then(|code| code.parse().ok())
+                .unwrap_or(ffi::SQLITE_CANTOPEN);
+            let msg = unsafe { std::ffi::CStr::from_ptr(ffi::sqlite3_errstr(code)) };
+            Error(libsql::Error::SqliteFailure(
+                code,
+                msg.to_string_lossy().into_owned(),
+            ))
+        }
+        err => Error::from(err),
+    })?;
+    let handle = OPENED_HANDLE.with(|handle| handle.replace(std::ptr::null_mut()));
+    Ok((conn, handle))
+}
+
+/// Interrupts a connection without locking it, as the query to abort holds
+/// the lock. Local connections are interrupted through their raw handle,
+/// which is safe to do from any thread.
+fn interrupt(
+    conn: &Mutex<libsql::Connection>,
+    handle: *mut ffi::sqlite3,
+    tasks: &TaskQueue,
+) -> std::result::Result<(), Error> {
+    tasks.interrupts.fetch_add(1, Ordering::SeqCst);
+    if !handle.is_null() {
+        unsafe { ffi::sqlite3_interrupt(handle) };
+        return Ok(());
+    }
+    // Remote connections don't support interrupting their requests, so
+    // there is nothing to abort while one holds the lock.
+    if let Ok(conn) = conn.try_lock() {
+        conn.interrupt()?;
+    }
+    Ok(())
+}
+
+/// Maps a SQLite result code into an error carrying the connection's error message.
+fn check_rc(handle: *mut ffi::sqlite3, rc: c_int) -> std::result::Result<(), Error> {
+    if rc == ffi::SQLITE_OK {
+        return Ok(());
+    }
+    let msg = unsafe { std::ffi::CStr::from_ptr(ffi::sqlite3_errmsg(handle)) }
+        .to_string_lossy()
+        .into_owned();
+    Err(Error(libsql::Error::SqliteFailure(rc, msg)))
+}
+
+/// Returns the number of declared parameters of a JS function.
+fn function_length(env: &Env, function: &JsFunction) -> Result<i32> {
+    let function = unsafe { napi::JsObject::from_raw_unchecked(env.raw(), function.raw()) };
+    function
+        .get_named_property::<napi::JsNumber>("length")?
+        .get_int32()
+}
+
+struct FunctionContext {
+    env: napi::sys::napi_env,
+    /// The JS function of a scalar function, the array of `start`, `step`,
+    /// `inverse` and `result` callbacks of an aggregate, or the factory of a
+    /// virtual table module.
+    callback: napi::Ref<()>,
+    safe_ints: bool,
+}
+
+struct TraceContext {
+    hook: ThreadsafeFunction<TraceEvent, ErrorStrategy::Fatal>,
+}
+
+unsafe extern "C" fn call_trace(
+    event: std::ffi::c_uint,
+    data: *mut c_void,
+    p: *mut c_void,
+    x: *mut c_void,
+) -> c_int {
+    let trace = &*(data as *const TraceContext);
+    let stmt = p as *mut ffi::sqlite3_stmt;
+    let text = |sql: *const c_char| {
+        (!sql.is_null()).then(|| std::ffi::CStr::from_ptr(sql).to_string_lossy().into_owned())
+    };
+    let sql = text(ffi::sqlite3_sql(stmt)).unwrap_or_default();
+    let event = match event as c_int {
+        ffi::SQLITE_TRACE_STMT => {
+            let expanded = ffi::sqlite3_expanded_sql(stmt);
+            let expanded_sql = text(expanded);
+            ffi::sqlite3_free(expanded as *mut c_void);
+            TraceEvent {
+                kind: "statement".to_string(),
+                sql,
+                expandedSql: expanded_sql,
+                duration: None,
+            }
+        }
+        ffi::SQLITE_TRACE_PROFILE => TraceEvent {
+            kind: "profile".to_string(),
+            sql,
+            expandedSql: None,
+            duration: Some(*(x as *const i64) as f64 / 1e9),
+        },
+        ffi::SQLITE_TRACE_ROW => TraceEvent {
+            kind: "row".to_string(),
+            sql,
+            expandedSql: None,
+            duration: None,
+        },
+        _ => return 0,
+    };
+    trace
+        .hook
+        .call(event, ThreadsafeFunctionCallMode::NonBlocking);
+    0
+}
+
+enum AuthorizerContext {
+    Hook {
+        env: napi::sys::napi_env,
+        hook: napi::Ref<()>,
+    },
+    Policy(Policy),
+}
+
+impl Drop for AuthorizerContext {
+    fn drop(&mut self) {
+        if let AuthorizerContext::Hook { env, hook } = self {
+            hook.unref(unsafe { Env::from_raw(*env) }).ok();
+        }
+    }
+}
+
+/// An `AuthorizerPolicy` whose actions and results are resolved to codes.
+struct Policy {
+    deny: Vec<PolicyRule>,
+    ignore: Vec<PolicyRule>,
+    allow: Vec<PolicyRule>,
+    default: c_int,
+}
+
+struct PolicyRule {
+    action: Option<c_int>,
+    table: Option<String>,
+    column: Option<String>,
+    database: Option<String>,
+}
+
+impl TryFrom<AuthorizerPolicy> for Policy {
+    type Error = napi::Error;
+
+    fn try_from(policy: AuthorizerPolicy) -> Result<Self> {
+        let rules = |rules: Option<Vec<AuthorizerRule>>| {
+            rules
+                .unwrap_or_default()
+                .into_iter()
+                .map(PolicyRule::try_from)
+                .collect::<Result<Vec<_>>>()
+        };
+        Ok(Policy {
+            deny: rules(policy.deny)?,
+            ignore: rules(policy.ignore)?,
+            allow: rules(policy.allow)?,
+            default: match policy.default.as_deref() {
+                Some(result) => authorization_code(result).ok_or_else(|| {
+                    napi::Error::new(
+                        napi::Status::InvalidArg,
+                        format!("Unknown authorization result: {}", result),
+                    )
+                })?,
+                None => ffi::SQLITE_OK,
+            },
+        })
+    }
+}
+
+impl TryFrom<AuthorizerRule> for PolicyRule {
+    type Error = napi::Error;
+
+    fn try_from(rule: AuthorizerRule) -> Result<Self> {
+        let action = match rule.action {
+            Some(name) => Some(
+                AUTHORIZER_ACTIONS
+                    .iter()
+                    .find(|(_, action)| name.eq_ignore_ascii_case(action))
+                    .map(|(code, _)| *code)
+                    .ok_or_else(|| {
+                        napi::Error::new(
+                            napi::Status::InvalidArg,
+                            format!("Unknown authorizer action: {}", name),
+                        )
+                    })?,
+            ),
+            None => None,
+        };
+        Ok(PolicyRule {
+            action,
+            table: rule.table,
+            column: rule.column,
+            database: rule.database,
+        })
+    }
+}
+
+impl Policy {
+    fn authorize(&self, request: &AuthorizerRequest) -> c_int {
+        let matches = |rules: &[PolicyRule]| rules.iter().any(|rule| rule.matches(request));
+        if matches(&self.deny) {
+            ffi::SQLITE_DENY
+        } else if matches(&self.ignore) {
+            ffi::SQLITE_IGNORE
+        } else if matches(&self.allow) {
+            ffi::SQLITE_OK
+        } else {
+            self.default
+        }
+    }
+}
+
+impl PolicyRule {
+    fn matches(&self, request: &AuthorizerRequest) -> bool {
+        // SQL identifiers are case-insensitive.
+        let matches = |expected: &Option<String>, name: Option<&str>| match expected {
+            Some(expected) => name.is_some_and(|name| expected.eq_ignore_ascii_case(name)),
+            None => true,
+        };
+        self.action.is_none_or(|action| action == request.action)
+            && matches(&self.table, request.table)
+            && matches(&self.column, request.column)
+            && matches(&self.database, request.database)
+    }
+}
+
+/// The arguments of an authorizer callback, decoded for its action.
+struct AuthorizerRequest<'a> {
+    action: c_int,
+    table: Option<&'a str>,
+    column: Option<&'a str>,
+    database: Option<&'a str>,
+    accessor: Option<&'a str>,
+}
+
+impl AuthorizerRequest<'_> {
+    unsafe fn new(
+        action: c_int,
+        arg1: *const c_char,
+        arg2: *const c_char,
+        database: *const c_char,
+        accessor: *const c_char,
+    ) -> Self {
+        let text = |arg: *const c_char| {
+            (!arg.is_null())
+                .then(|| std::ffi::CStr::from_ptr(arg).to_str().ok())
+                .flatten()
+        };
+        let (table, column) = match action {
+            ffi::SQLITE_READ | ffi::SQLITE_UPDATE => (text(arg1), text(arg2)),
+            ffi::SQLITE_CREATE_TABLE
+            | ffi::SQLITE_CREATE_TEMP_TABLE
+            | ffi::SQLITE_DROP_TABLE
+            | ffi::SQLITE_DROP_TEMP_TABLE
+            | ffi::SQLITE_INSERT
+            | ffi::SQLITE_DELETE
+            | ffi::SQLITE_ANALYZE
+            | ffi::SQLITE_CREATE_VTABLE
+            | ffi::SQLITE_DROP_VTABLE => (text(arg1), None),
+            ffi::SQLITE_CREATE_INDEX
+            | ffi::SQLITE_CREATE_TEMP_INDEX
+            | ffi::SQLITE_DROP_INDEX
+            | ffi::SQLITE_DROP_TEMP_INDEX
+            | ffi::SQLITE_CREATE_TRIGGER
+            | ffi::SQLITE_CREATE_TEMP_TRIGGER
+            | ffi::SQLITE_DROP_TRIGGER
+            | ffi::SQLITE_DROP_TEMP_TRIGGER
+            | ffi::SQLITE_ALTER_TABLE => (text(arg2), None),
+            _ => (None, None),
+        };
+        AuthorizerRequest {
+            action,
+            table,
+            column,
+            database: text(database),
+            accessor: text(accessor),
+        }
+    }
+}
+
+unsafe extern "C" fn call_authorizer(
+    data: *mut c_void,
+    action: c_int,
+    arg1: *const c_char,
+    arg2: *const c_char,
+    database: *const c_char,
+    accessor: *const c_char,
+) -> c_int {
+    let request = AuthorizerRequest::new(action, arg1, arg2, database, accessor);
+    match &*(data as *const AuthorizerContext) {
+        AuthorizerContext::Policy(policy) => policy.authorize(&request),
+        AuthorizerContext::Hook { env, hook } => call_authorizer_hook(*env, hook, &request),
+    }
+}
+
+unsafe fn call_authorizer_hook(
+    env: napi::sys::napi_env,
+    hook: &napi::Ref<()>,
+    request: &AuthorizerRequest,
+) -> c_int {
+    let args = AuthorizerArgs {
+        action: authorizer_action_name(request.action),
+        table: request.table.map(str::to_owned),
+        column: request.column.map(str::to_owned),
+        database: request.database.map(str::to_owned),
+        accessor: request.accessor.map(str::to_owned),
+    };
+    let result = (|| {
+        let env = callback_env(env)?;
+        let hook: JsFunction = env.get_reference_value(hook)?;
+        let args = JsUnknown::from_raw_unchecked(
+            env.raw(),
+            AuthorizerArgs::to_napi_value(env.raw(), args)?,
+        );
+        let result = match hook.call(None, &[args]) {
+            Ok(result) => result,
+            Err(err) => {
+                // Leave the exception pending, so that it is thrown instead of
+                // the authorization error.
+                callback_error(env.raw(), err);
+                return Ok(None);
+            }
+        };
+        if result.get_type()? != ValueType::String {
+            return Ok(None);
+        }
+        Ok::<_, napi::Error>(Some(result.coerce_to_string()?.into_utf8()?.into_owned()?))
+    })();
+    result
+        .ok()
+        .flatten()
+        .and_then(|result| authorization_code(&result))
+        .unwrap_or(ffi::SQLITE_DENY)
+}
+
+/// Returns the authorizer return code of an `"allow"`, `"deny"` or
+/// `"ignore"` result.
+fn authorization_code(result: &str) -> Option<c_int> {
+    match result {
+        "allow" => Some(ffi::SQLITE_OK),
+        "deny" => Some(ffi::SQLITE_DENY),
+        "ignore" => Some(ffi::SQLITE_IGNORE),
+        _ => None,
+    }
+}
+
+const AUTHORIZER_ACTIONS: [(c_int, &str); 33] = [
+    (ffi::SQLITE_CREATE_INDEX, "SQLITE_CREATE_INDEX"),
+    (ffi::SQLITE_CREATE_TABLE, "SQLITE_CREATE_TABLE"),
+    (ffi::SQLITE_CREATE_TEMP_INDEX, "SQLITE_CREATE_TEMP_INDEX"),
+    (ffi::SQLITE_CREATE_TEMP_TABLE, "SQLITE_CREATE_TEMP_TABLE"),
+    (
+        ffi::SQLITE_CREATE_TEMP_TRIGGER,
+        "SQLITE_CREATE_TEMP_TRIGGER",
+    ),
+    (ffi::SQLITE_CREATE_TEMP_VIEW, "SQLITE_CREATE_TEMP_VIEW"),
+    (ffi::SQLITE_CREATE_TRIGGER, "SQLITE_CREATE_TRIGGER"),
+    (ffi::SQLITE_CREATE_VIEW, "SQLITE_CREATE_VIEW"),
+    (ffi::SQLITE_DELETE, "SQLITE_DELETE"),
+    (ffi::SQLITE_DROP_INDEX, "SQLITE_DROP_INDEX"),
+    (ffi::SQLITE_DROP_TABLE, "SQLITE_DROP_TABLE"),
+    (ffi::SQLITE_DROP_TEMP_INDEX, "SQLITE_DROP_TEMP_INDEX"),
+    (ffi::SQLITE_DROP_TEMP_TABLE, "SQLITE_DROP_TEMP_TABLE"),
+    (ffi::SQLITE_DROP_TEMP_TRIGGER, "SQLITE_DROP_TEMP_TRIGGER"),
+    (ffi::SQLITE_DROP_TEMP_VIEW, "SQLITE_DROP_TEMP_VIEW"),
+    (ffi::SQLITE_DROP_TRIGGER, "SQLITE_DROP_TRIGGER"),
+    (ffi::SQLITE_DROP_VIEW, "SQLITE_DROP_VIEW"),
+    (ffi::SQLITE_INSERT, "SQLITE_INSERT"),
+    (ffi::SQLITE_PRAGMA, "SQLITE_PRAGMA"),
+    (ffi::SQLITE_READ, "SQLITE_READ"),
+    (ffi::SQLITE_SELECT, "SQLITE_SELECT"),
+    (ffi::SQLITE_TRANSACTION, "SQLITE_TRANSACTION"),
+    (ffi::SQLITE_UPDATE, "SQLITE_UPDATE"),
+    (ffi::SQLITE_ATTACH, "SQLITE_ATTACH"),
+    (ffi::SQLITE_DETACH, "SQLITE_DETACH"),
+    (ffi::SQLITE_ALTER_TABLE, "SQLITE_ALTER_TABLE"),
+    (ffi::SQLITE_REINDEX, "SQLITE_REINDEX"),
+    (ffi::SQLITE_ANALYZE, "SQLITE_ANALYZE"),
+    (ffi::SQLITE_CREATE_VTABLE, "SQLITE_CREATE_VTABLE"),
+    (ffi::SQLITE_DROP_VTABLE, "SQLITE_DROP_VTABLE"),
+    (ffi::SQLITE_FUNCTION, "SQLITE_FUNCTION"),
+    (ffi::SQLITE_SAVEPOINT, "SQLITE_SAVEPOINT"),
+    (ffi::SQLITE_RECURSIVE, "SQLITE_RECURSIVE"),
+];
+
+/// Returns the name of an authorizer action code.
+fn authorizer_action_name(action: c_int) -> String {
+    match AUTHORIZER_ACTIONS.iter().find(|(code, _)| *code == action) {
+        Some((_, name)) => name.to_string(),
+        None => format!("UNKNOWN_ACTION_{}", action),
+    }
+}
+
+const AGGREGATE_START: u32 = 0;
+const AGGREGATE_STEP: u32 = 1;
+const AGGREGATE_INVERSE: u32 = 2;
+const AGGREGATE_RESULT: u32 = 3;
+
+unsafe extern "C" fn call_function(
+    ctx: *mut ffi::sqlite3_context,
+    argc: c_int,
+    argv: *mut *mut ffi::sqlite3_value,
+) {
+    let function = &*(ffi::sqlite3_user_data(ctx) as *const FunctionContext);
+    let result = (|| {
+        let env = callback_env(function.env)?;
+        let callback: JsFunction = env.get_reference_value(&function.callback)?;
+        let args = convert_args(&env, function.safe_ints, argc, argv)?;
+        map_value(callback.call(None, &args)?)
+    })();
+    set_result(function.env, ctx, result);
+}
+
+unsafe extern "C" fn destroy_function(function: *mut c_void) {
+    let mut function = Box::from_raw(function as *mut FunctionContext);
+    function.callback.unref(Env::from_raw(function.env)).ok();
+}
+
+unsafe extern "C" fn aggregate_step(
+    ctx: *mut ffi::sqlite3_context,
+    argc: c_int,
+    argv: *mut *mut ffi::sqlite3_value,
+) {
+    aggregate_accumulate(ctx, AGGREGATE_STEP, argc, argv);
+}
+
+unsafe extern "C" fn aggregate_inverse(
+    ctx: *mut ffi::sqlite3_context,
+    argc: c_int,
+    argv: *mut *mut ffi::sqlite3_value,
+) {
+    aggregate_accumulate(ctx, AGGREGATE_INVERSE, argc, argv);
+}
+
+unsafe extern "C" fn aggregate_value(ctx: *mut ffi::sqlite3_context) {
+    let aggregate = &*(ffi::sqlite3_user_data(ctx) as *const FunctionContext);
+    set_result(aggregate.env, ctx, aggregate_result(ctx));
+}
+
+unsafe extern "C" fn aggregate_final(ctx: *mut ffi::sqlite3_context) {
+    let result = aggregate_result(ctx);
+    let aggregate = &*(ffi::sqlite3_user_data(ctx) as *const FunctionContext);
+    let slot = ffi::sqlite3_aggregate_context(ctx, 0) as *mut *mut napi::Ref<()>;
+    if !slot.is_null() && !(*slot).is_null() {
+        let mut state = Box::from_raw(*slot);
+        state.unref(Env::from_raw(aggregate.env)).ok();
+        *slot = std::ptr::null_mut();
+    }
+    set_result(aggregate.env, ctx, result);
+}
+
+/// Calls the `step` or `inverse` callback of an aggregate, replacing the
+/// accumulator with the returned value unless it is `undefined`.
+unsafe fn aggregate_accumulate(
+    ctx: *mut ffi::sqlite3_context,
+    callback: u32,
+    argc: c_int,
+    argv: *mut *mut ffi::sqlite3_value,
+) {
+    let aggregate = &*(ffi::sqlite3_user_data(ctx) as *const FunctionContext);
+    let result = (|| {
+        let env = callback_env(aggregate.env)?;
+        let mut state = aggregate_state(ctx, &env, aggregate)?;
+        let callbacks: napi::JsObject = env.get_reference_value(&aggregate.callback)?;
+        let callback = callbacks.get_element::<JsFunction>(callback)?;
+        let mut args = vec![state.get_element::<JsUnknown>(0)?];
+        args.extend(convert_args(&env, aggregate.safe_ints, argc, argv)?);
+        let accumulator = callback.call(None, &args)?;
+        if accumulator.get_type()? != ValueType::Undefined {
+            state.set_element(0, accumulator)?;
+        }
+        Ok(())
+    })();
+    if let Err(e) = result {
+        set_result(aggregate.env, ctx, Err(e));
+    }
+}
+
+/// Computes the current value of an aggregate with its `result` callback, or
+/// returns the accumulator as is if there is none.
+unsafe fn aggregate_result(ctx: *mut ffi::sqlite3_context) -> Result<libsql::Value> {
+    let aggregate = &*(ffi::sqlite3_user_data(ctx) as *const FunctionContext);
+    let env = callback_env(aggregate.env)?;
+    let state = aggregate_state(ctx, &env, aggregate)?;
+    let accumulator = state.get_element::<JsUnknown>(0)?;
+    let callbacks: napi::JsObject = env.get_reference_value(&aggregate.callback)?;
+    let result = callbacks.get_element::<JsUnknown>(AGGREGATE_RESULT)?;
+    if result.get_type()? == ValueType::Function {
+        let result: JsFunction = result.cast();
+        map_value(result.call(None, &[accumulator])?)
+    } else {
+        map_value(accumulator)
+    }
+}
+
+/// Returns the JS array holding the accumulator of the aggregate being
+/// evaluated, initializing it from `start` on first use.
+unsafe fn aggregate_state(
+    ctx: *mut ffi::sqlite3_context,
+    env: &Env,
+    aggregate: &FunctionContext,
+) -> Result<napi::JsObject> {
+    let size = std::mem::size_of::<*mut napi::Ref<()>>() as c_int;
+    let slot = ffi::sqlite3_aggregate_context(ctx, size) as *mut *mut napi::Ref<()>;
+    if slot.is_null() {
+        return Err(napi::Error::from_reason("out of memory"));
+    }
+    if (*slot).is_null() {
+        let callbacks: napi::JsObject = env.get_reference_value(&aggregate.callback)?;
+        let mut start = callbacks.get_element::<JsUnknown>(AGGREGATE_START)?;
+        if start.get_type()? == ValueType::Function {
+            let start_fn: JsFunction = start.cast();
+            start = start_fn.call_without_args(None)?;
+        }
+        let mut state = env.create_array_with_length(1)?;
+        state.set_element(0, start)?;
+        *slot = Box::into_raw(Box::new(env.create_reference(state)?));
+    }
+    env.get_reference_value(&**slot)
+}
+
+static TABLE_MODULE: ffi::sqlite3_module = table_module(false);
+static EPONYMOUS_TABLE_MODULE: ffi::sqlite3_module = table_module(true);
+
+const fn table_module(eponymous: bool) -> ffi::sqlite3_module {
+    ffi::sqlite3_module {
+        iVersion: 0,
+        // Leaving out `xCreate` makes the module eponymous-only.
+        xCreate: if eponymous { None } else { Some(table_connect) },
+        xConnect: Some(table_connect),
+        xBestIndex: Some(table_best_index),
+        xDisconnect: Some(table_disconnect),
+        xDestroy: Some(table_disconnect),
+        xOpen: Some(table_open),
+        xClose: Some(table_close),
+        xFilter: Some(table_filter),
+        xNext: Some(table_next),
+        xEof: Some(table_eof),
+        xColumn: Some(table_column),
+        xRowid: Some(table_rowid),
+        xUpdate: None,
+        xBegin: None,
+        xSync: None,
+        xCommit: None,
+        xRollback: None,
+        xFindFunction: None,
+        xRename: None,
+        xSavepoint: None,
+        xRelease: None,
+        xRollbackTo: None,
+        xShadowName: None,
+        xIntegrity: None,
+        reserved: [None; 5],
+        xPreparedSql: None,
+    }
+}
+
+#[repr(C)]
+struct VirtualTable {
+    base: ffi::sqlite3_vtab,
+    env: napi::sys::napi_env,
+    /// The generator function yielding the rows of the table, each as an array
+    /// of parameter values followed by column values.
+    rows: napi::Ref<()>,
+    parameter_count: c_int,
+    safe_ints: bool,
+}
+
+#[repr(C)]
+struct VirtualTableCursor {
+    base: ffi::sqlite3_vtab_cursor,
+    /// Array holding the iterator of the current scan and the current row.
+    state: Option<napi::Ref<()>>,
+    rowid: i64,
+    eof: bool,
+}
+
+/// Reports an error message to SQLite through a C string allocated with
+/// `sqlite3_mprintf`, which SQLite frees.
+unsafe fn sqlite_error_message(message: &str) -> *mut c_char {
+    let message = std::ffi::CString::new(message.replace('\0', "")).unwrap_or_default();
+    ffi::sqlite3_mprintf(c"%s".as_ptr(), message.as_ptr())
+}
+
+unsafe fn set_table_error(vtab: *mut ffi::sqlite3_vtab, message: &str) {
+    ffi::sqlite3_free((*vtab).zErrMsg as *mut c_void);
+    (*vtab).zErrMsg = sqlite_error_message(message);
+}
+
+unsafe extern "C" fn table_connect(
+    db: *mut ffi::sqlite3,
+    aux: *mut c_void,
+    argc: c_int,
+    argv: *const *const c_char,
+    vtab: *mut *mut ffi::sqlite3_vtab,
+    err: *mut *mut c_char,
+) -> c_int {
+    let module = &*(aux as *const FunctionContext);
+    let result = (|| -> Result<Box<VirtualTable>> {
+        let env = callback_env(module.env)?;
+        let factory: JsFunction = env.get_reference_value(&module.callback)?;
+        let mut args = Vec::with_capacity(argc as usize);
+        for idx in 0..argc as usize {
+            let arg = std::ffi::CStr::from_ptr(*argv.add(idx)).to_string_lossy();
+            args.push(env.create_string(&arg)?);
+        }
+        let definition = factory.call(None, &args)?.coerce_to_object()?;
+        let sql = definition
+            .get_named_property::<napi::JsString>("sql")?
+            .into_utf8()?
+            .into_owned()?;
+        let rows = definition.get_named_property::<JsFunction>("rows")?;
+        let parameter_count = definition
+            .get_named_property::<napi::JsNumber>("parameterCount")?
+            .get_int32()?;
+        let safe_ints = definition
+            .get::<_, bool>("safeIntegers")?
+            .unwrap_or(module.safe_ints);
+        let direct_only = definition.get::<_, bool>("directOnly")?.unwrap_or(false);
+        let sql = std::ffi::CString::new(sql)?;
+        let rc = ffi::sqlite3_declare_vtab(db, sql.as_ptr());
+        check_rc(db, rc).map_err(|e| e.throw(&env))?;
+        if direct_only {
+            ffi::sqlite3_vtab_config(db, ffi::SQLITE_VTAB_DIRECTONLY);
+        }
+        Ok(Box::new(VirtualTable {
+            base: std::mem::zeroed(),
+            env: module.env,
+            rows: env.create_reference(rows)?,
+            parameter_count,
+            safe_ints,
+        }))
+    })();
+    match result {
+        Ok(table) => {
+            *vtab = Box::into_raw(table) as *mut ffi::sqlite3_vtab;
+            ffi::SQLITE_OK
+        }
+        Err(e) => {
+            *err = sqlite_error_message(&callback_error(module.env, e));
+            ffi::SQLITE_ERROR
+        }
+    }
+}
+
+unsafe extern "C" fn table_disconnect(vtab: *mut ffi::sqlite3_vtab) -> c_int {
+    let mut table = Box::from_raw(vtab as *mut VirtualTable);
+    table.rows.unref(Env::from_raw(table.env)).ok();
+    ffi::SQLITE_OK
+}
+
+/// Passes equality constraints on the hidden parameter columns to `xFilter` as
+/// arguments, in parameter order, recording which were given in `idxNum`.
+unsafe extern "C" fn table_best_index(
+    vtab: *mut ffi::sqlite3_vtab,
+    info: *mut ffi::sqlite3_index_info,
+) -> c_int {
+    let table = &*(vtab as *const VirtualTable);
+    let info = &mut *info;
+    let mut forwarded = vec![];
+    for idx in 0..info.nConstraint as usize {
+        let constraint = &*info.aConstraint.add(idx);
+        if constraint.iColumn < 0 || constraint.iColumn >= table.parameter_count {
+            continue;
+        }
+        if constraint.op as c_int != ffi::SQLITE_INDEX_CONSTRAINT_EQ {
+            set_table_error(
+                vtab,
+                "virtual table parameters can only be constrained by the '=' operator",
+            );
+            return ffi::SQLITE_ERROR;
+        }
+        if constraint.usable == 0 {
+            // Don't allow plans that would leave out arguments the query gave.
+            return ffi::SQLITE_CONSTRAINT;
+        }
+        forwarded.push((constraint.iColumn, idx));
+    }
+    forwarded.sort();
+    let mut argument_count = 0;
+    for (column, idx) in forwarded {
+        let bit = 1 << column;
+        if info.idxNum & bit == 0 {
+            info.idxNum |= bit;
+            argument_count += 1;
+            let usage = &mut *info.aConstraintUsage.add(idx);
+            usage.argvIndex = argument_count;
+            usage.omit = 1;
+        }
+    }
+    // A high estimated cost keeps SQLite from running the generator within a
+    // loop if it can be avoided.
+    info.estimatedCost = 1000000000.0 / (argument_count + 1) as f64;
+    info.estimatedRows = 1000000000 / (argument_count as i64 + 1);
+    ffi::SQLITE_OK
+}
+
+unsafe extern "C" fn table_open(
+    _vtab: *mut ffi::sqlite3_vtab,
+    cursor: *mut *mut ffi::sqlite3_vtab_cursor,
+) -> c_int {
+    let table_cursor = Box::new(VirtualTableCursor {
+        base: std::mem::zeroed(),
+        state: None,
+        rowid: 0,
+        eof: true,
+    });
+    *cursor = Box::into_raw(table_cursor) as *mut ffi::sqlite3_vtab_cursor;
+    ffi::SQLITE_OK
+}
+
+unsafe extern "C" fn table_close(cursor: *mut ffi::sqlite3_vtab_cursor) -> c_int {
+    let table = &*((*cursor).pVtab as *const VirtualTable);
+    let mut cursor = Box::from_raw(cursor as *mut VirtualTableCursor);
+    if let Some(mut state) = cursor.state.take() {
+        state.unref(Env::from_raw(table.env)).ok();
+    }
+    ffi::SQLITE_OK
+}
+
+unsafe extern "C" fn table_filter(
+    cursor: *mut ffi::sqlite3_vtab_cursor,
+    idx_num: c_int,
+    _idx_str: *const c_char,
+    _argc: c_int,
+    argv: *mut *mut ffi::sqlite3_value,
+) -> c_int {
+    let vtab = (*cursor).pVtab;
+    let table = &*(vtab as *const VirtualTable);
+    let table_cursor = &mut *(cursor as *mut VirtualTableCursor);
+    let result = (|| {
+        let env = callback_env(table.env)?;
+        if let Some(mut state) = table_cursor.state.take() {
+            state.unref(env).ok();
+        }
+        let mut args = Vec::with_capacity(table.parameter_count as usize);
+        let mut next_arg = 0;
+        for idx in 0..table.parameter_count {
+            if idx_num & (1 << idx) != 0 {
+                let value = value_from_raw(*argv.add(next_arg));
+                args.push(convert_value(&env, table.safe_ints, value)?);
+                next_arg += 1;
+            } else {
+                args.push(env.get_undefined()?.into_unknown());
+            }
+        }
+        let rows: JsFunction = env.get_reference_value(&table.rows)?;
+        let iterator = rows.call(None, &args)?;
+        let mut state = env.create_array_with_length(2)?;
+        state.set_element(0, iterator)?;
+        table_cursor.state = Some(env.create_reference(state)?);
+        table_cursor.rowid = 0;
+        table_advance(&env, table_cursor)
+    })();
+    match result {
+        Ok(()) => ffi::SQLITE_OK,
+        Err(e) => {
+            set_table_error(vtab, &callback_error(table.env, e));
+            ffi::SQLITE_ERROR
+        }
+    }
+}
+
+unsafe extern "C" fn table_next(cursor: *mut ffi::sqlite3_vtab_cursor) -> c_int {
+    let vtab = (*cursor).pVtab;
+    let table = &*(vtab as *const VirtualTable);
+    let table_cursor = &mut *(cursor as *mut VirtualTableCursor);
+    match callback_env(table.env).and_then(|env| table_advance(&env, table_cursor)) {
+        Ok(()) => ffi::SQLITE_OK,
+        Err(e) => {
+            set_table_error(vtab, &callback_error(table.env, e));
+            ffi::SQLITE_ERROR
+        }
+    }
+}
+
+/// Moves the cursor to the next row yielded by the rows generator.
+fn table_advance(env: &Env, cursor: &mut VirtualTableCursor) -> Result<()> {
+    let mut state: napi::JsObject = match &cursor.state {
+        Some(state) => env.get_reference_value(state)?,
+        None => return Ok(()),
+    };
+    let iterator = state.get_element::<napi::JsObject>(0)?;
+    let next = iterator.get_named_property::<JsFunction>("next")?;
+    let result = next
+        .call_without_args(Some(&iterator))?
+        .coerce_to_object()?;
+    cursor.eof = result
+        .get_named_property::<napi::JsBoolean>("done")?
+        .get_value()?;
+    if !cursor.eof {
+        state.set_element(1, result.get_named_property::<JsUnknown>("value")?)?;
+        cursor.rowid += 1;
+    }
+    Ok(())
+}
+
+unsafe extern "C" fn table_eof(cursor: *mut ffi::sqlite3_vtab_cursor) -> c_int {
+    (*(cursor as *const VirtualTableCursor)).eof as c_int
+}
+
+unsafe extern "C" fn table_column(
+    cursor: *mut ffi::sqlite3_vtab_cursor,
+    ctx: *mut ffi::sqlite3_context,
+    column: c_int,
+) -> c_int {
+    let table = &*((*cursor).pVtab as *const VirtualTable);
+    let table_cursor = &*(cursor as *const VirtualTableCursor);
+    let result = (|| {
+        let env = callback_env(table.env)?;
+        let state: napi::JsObject = match &table_cursor.state {
+            Some(state) => env.get_reference_value(state)?,
+            None => return Ok(libsql::Value::Null),
+        };
+        let row = state.get_element::<napi::JsObject>(1)?;
+        map_value(row.get_element::<JsUnknown>(column as u32)?)
+    })();
+    set_result(table.env, ctx, result);
+    ffi::SQLITE_OK
+}
+
+unsafe extern "C" fn table_rowid(
+    cursor: *mut ffi::sqlite3_vtab_cursor,
+    rowid: *mut ffi::sqlite3_int64,
+) -> c_int {
+    *rowid = (*(cursor as *const VirtualTableCursor)).rowid;
+    ffi::SQLITE_OK
+}
+
+/// Converts the arguments of a user-defined function call into JS values.
+unsafe fn convert_args(
+    env: &Env,
+    safe_ints: bool,
+    argc: c_int,
+    argv: *mut *mut ffi::sqlite3_value,
+) -> Result<Vec<JsUnknown>> {
+    let mut args = Vec::with_capacity(argc as usize);
+    for idx in 0..argc as usize {
+        let value = value_from_raw(*argv.add(idx));
+        args.push(convert_value(env, safe_ints, value)?);
+    }
+    Ok(args)
+}
+
+/// Reads a SQLite value passed to a user-defined function.
+unsafe fn value_from_raw(value: *mut ffi::sqlite3_value) -> libsql::Value {
+    match ffi::sqlite3_value_type(value) {
+        ffi::SQLITE_INTEGER => libsql::Value::Integer(ffi::sqlite3_value_int64(value)),
+        ffi::SQLITE_FLOAT => libsql::Value::Real(ffi::sqlite3_value_double(value)),
+        ffi::SQLITE_TEXT => {
+            let text = ffi::sqlite3_value_text(value);
+            let len = ffi::sqlite3_value_bytes(value) as usize;
+            let bytes = std::slice::from_raw_parts(text, len);
+            libsql::Value::Text(String::from_utf8_lossy(bytes).into_owned())
+        }
+        ffi::SQLITE_BLOB => {
+            let blob = ffi::sqlite3_value_blob(value) as *const u8;
+            let len = ffi::sqlite3_value_bytes(value) as usize;
+            if blob.is_null() || len == 0 {
+                libsql::Value::Blob(vec![])
+            } else {
+                libsql::Value::Blob(std::slice::from_raw_parts(blob, len).to_vec())
+            }
+        }
+        _ => libsql::Value::Null,
+    }
+}
+
+/// Reports the outcome of a user-defined function call back to SQLite.
+unsafe fn set_result(
+    env: napi::sys::napi_env,
+    ctx: *mut ffi::sqlite3_context,
+    result: Result<libsql::Value>,
+) {
+    match result {
+        Ok(libsql::Value::Null) => ffi::sqlite3_result_null(ctx),
+        Ok(libsql::Value::Integer(v)) => ffi::sqlite3_result_int64(ctx, v),
+        Ok(libsql::Value::Real(v)) => ffi::sqlite3_result_double(ctx, v),
+        Ok(libsql::Value::Text(v)) => ffi::sqlite3_result_text(
+            ctx,
+            v.as_ptr() as *const c_char,
+            v.len() as c_int,
+            ffi::SQLITE_TRANSIENT(),
+        ),
+        Ok(libsql::Value::Blob(v)) => ffi::sqlite3_result_blob(
+            ctx,
+            v.as_ptr() as *const c_void,
+            v.len() as c_int,
+            ffi::SQLITE_TRANSIENT(),
+        ),
+        Err(e) => {
+            let message = callback_error(env, e);
+            ffi::sqlite3_result_error(
+                ctx,
+                message.as_ptr() as *const c_char,
+                message.len() as c_int,
+            )
+        }
+    }
+}
+
+/// Returns the flags to open a local database with, which interpret `file:`
+/// paths as URIs. The connection stays in libSQL's serialized threading mode,
+/// which asynchronous queries and the hooks on the raw handle rely on.
+fn open_flags(env: &Env, path: &str, opts: Option<&Options>) -> Result<libsql::OpenFlags> {
+    let readonly = opts.and_then(|opts| opts.readonly).unwrap_or(false);
+    let file_must_exist = opts.and_then(|opts| opts.fileMustExist).unwrap_or(false);
+    let mut flags = match opts.and_then(|opts| opts.flags) {
+        Some(flags) => flags,
+        None if readonly => ffi::SQLITE_OPEN_READONLY,
+        None if file_must_exist => ffi::SQLITE_OPEN_READWRITE,
+        None => ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE,
+    };
+    let modes = ffi::SQLITE_OPEN_NOMUTEX
+        | ffi::SQLITE_OPEN_FULLMUTEX
+        | ffi::SQLITE_OPEN_SHAREDCACHE
+        | ffi::SQLITE_OPEN_PRIVATECACHE;
+    if flags & modes != 0 {
+        return Err(throw_open_flags_error(env));
+    }
+    if path.starts_with("file:") {
+        flags |= ffi::SQLITE_OPEN_URI;
+    }
+    Ok(libsql::OpenFlags::from_bits_retain(flags))
+}
+
+/// Builds the encryption-at-rest configuration of a local database or replica.
+fn encryption_config(opts: Option<&Options>) -> Result<Option<libsql::EncryptionConfig>> {
+    let key = match opts.and_then(|opts| opts.encryptionKey.as_ref()) {
+        Some(Either::A(key)) => key.as_bytes().to_vec(),
+        Some(Either::B(key)) => key.to_vec(),
+        None => return Ok(None),
+    };
+    let cipher = match opts.and_then(|opts| opts.encryptionCipher.as_deref()) {
+        Some(cipher) => cipher.parse::<libsql::Cipher>().map_err(|_| {
+            napi::Error::from_reason(format!("Unsupported encryption cipher: {}", cipher))
+        })?,
+        None => libsql::Cipher::default(),
+    };
+    Ok(Some(libsql::EncryptionConfig::new(cipher, key.into())))
+}
+
+fn is_remote_path(path: &str) -> bool {
+    path.starts_with("libsql://") || path.starts_with("http://") || path.starts_with("https://")
+}
+
+/// Rejects the options that only apply to local databases and replicas.
+fn check_remote_options(env: &Env, opts: Option<&Options>) -> Result<()> {
+    let Some(opts) = opts else {
+        return Ok(());
+    };
+    let unsupported = [
+        ("timeout", opts.timeout.is_some()),
+        ("verbose", opts.verbose.is_some()),
+        ("encryptionKey", opts.encryptionKey.is_some()),
+        ("encryptionCipher", opts.encryptionCipher.is_some()),
+        ("readonly", opts.readonly.is_some()),
+        ("fileMustExist", opts.fileMustExist.is_some()),
+        ("flags", opts.flags.is_some()),
+        ("syncUrl", opts.syncUrl.is_some()),
+        ("syncPeriod", opts.syncPeriod.is_some()),
+    ];
+    match unsupported.iter().find(|(_, given)| *given) {
+        Some((name, _)) => Err(throw_unsupported_option_error(
+            env,
+            name,
+            "remote databases",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Rejects the options of local databases that embedded replicas cannot be
+/// opened with.
+fn check_replica_options(env: &Env, opts: Option<&Options>) -> Result<()> {
+    let Some(opts) = opts else {
+        return Ok(());
+    };
+    let unsupported = [
+        ("readonly", opts.readonly.is_some()),
+        ("fileMustExist", opts.fileMustExist.is_some()),
+        ("flags", opts.flags.is_some()),
+    ];
+    match unsupported.iter().find(|(_, given)| *given) {
+        Some((name, _)) => Err(throw_unsupported_option_error(
+            env,
+            name,
+            "embedded replicas",
+        )),
+        None => Ok(()),
+    }
 }
 
 fn throw_database_closed_error(env: &Env) -> napi::Error {
     let msg = "The database connection is not open";
     let err = napi::Error::new(napi::Status::InvalidArg, msg.to_string());
+    env.throw_type_error(msg, None).unwrap();
+    err
+}
+
+fn throw_unsupported_option_error(env: &Env, option: &str, databases: &str) -> napi::Error {
+    let msg = format!("The {} option is not supported by {}", option, databases);
+    let err = napi::Error::new(napi::Status::InvalidArg, msg.clone());
+    env.throw_type_error(&msg, None).unwrap();
+    err
+}
+
+fn throw_open_flags_error(env: &Env) -> napi::Error {
+    let msg = "The flags option cannot set the threading or cache mode of the connection";
+    let err = napi::Error::new(napi::Status::InvalidArg, msg.to_string());
+    env.throw_type_error(msg, None).unwrap();
+    err
+}
+
+fn throw_async_authorizer_error(env: &Env) -> napi::Error {
+    let msg = "JavaScript authorizers cannot be used with asynchronous queries, use an authorizer policy instead";
+    let err = napi::Error::new(napi::Status::InvalidArg, msg.to_string());
+    env.throw_type_error(msg, None).unwrap();
+    err
+}
+
+fn throw_invalid_pragma_error(env: &Env, source: &str) -> napi::Error {
+    let msg = format!("Invalid pragma: {}", source);
+    let err = napi::Error::new(napi::Status::InvalidArg, msg.clone());
     env.throw_type_error(&msg, None).unwrap();
     err
 }
 
+/// Builds the statement of a pragma from its source, which must start with a
+/// pragma name, optionally qualified by a schema name, and may be followed by
+/// `= value` or `(value)`, where the value is a single identifier, number or
+/// quoted string.
+fn pragma_sql(source: &str) -> Option<String> {
+    let source = source.trim();
+    let end = source.find(['=', '(']).unwrap_or(source.len());
+    let (name, argument) = source.split_at(end);
+    let name = name.trim_end();
+    let valid = match name.split_once('.') {
+        Some((schema, name)) => is_pragma_identifier(schema) && is_pragma_identifier(name),
+        None => is_pragma_identifier(name),
+    };
+    if !valid {
+        return None;
+    }
+    let argument = if let Some(value) = argument.strip_prefix('=') {
+        format!(" = {}", pragma_value(value)?)
+    } else if let Some(value) = argument.strip_prefix('(') {
+        format!("({})", pragma_value(value.strip_suffix(')')?)?)
+    } else {
+        String::new()
+    };
+    Some(format!("PRAGMA {}{}", name, argument))
+}
+
+fn is_pragma_identifier(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Returns the argument of a pragma if it is a single token: an identifier,
+/// an optionally signed number, or a quoted string.
+fn pragma_value(value: &str) -> Option<&str> {
+    let value = value.trim();
+    let digits = value.strip_prefix(['+', '-']).unwrap_or(value);
+    let is_number = digits.starts_with(|c: char| c.is_ascii_digit())
+        && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && digits.parse::<f64>().is_ok();
+    let is_string = ['\'', '"'].into_iter().any(|quote| {
+        value.len() >= 2
+            && value.starts_with(quote)
+            && value.ends_with(quote)
+            && !value[1..value.len() - 1]
+                .replace(&format!("{}{}", quote, quote), "")
+                .contains(quote)
+    });
+    (is_pragma_identifier(value) || is_number || is_string).then_some(value)
+}
+
+/// Runs the statement of a pragma, returning its columns and rows.
+async fn pragma_rows(
+    conn: &Mutex<libsql::Connection>,
+    sql: &str,
+) -> std::result::Result<(Vec<String>, Vec<Vec<libsql::Value>>), Error> {
+    let conn = conn.lock().await;
+    let mut rows = conn.query(sql, ()).await?;
+    let columns = (0..rows.column_count())
+        .map(|idx| rows.column_name(idx).unwrap_or_default().to_string())
+        .collect::<Vec<_>>();
+    let mut values = vec![];
+    while let Some(row) = rows.next().await? {
+        values.push(row_values(&rows, &row)?);
+    }
+    Ok((columns, values))
+}
+
+/// Converts the rows of a pragma to its JS result, which is the first value
+/// of the first row in `simple` mode.
+fn pragma_result(
+    env: &Env,
+    columns: Vec<String>,
+    rows: Vec<Vec<libsql::Value>>,
+    safe_ints: bool,
+    simple: bool,
+) -> Result<JsUnknown> {
+    if simple {
+        return match rows.into_iter().next() {
+            Some(row) => convert_row_values(env, &columns, row, safe_ints, false, true),
+            None => Ok(env.get_undefined()?.into_unknown()),
+        };
+    }
+    let mut js_array = env.create_array_with_length(rows.len())?;
+    for (idx, row) in rows.into_iter().enumerate() {
+        let row = convert_row_values(env, &columns, row, safe_ints, false, false)?;
+        js_array.set_element(idx as u32, row)?;
+    }
+    Ok(js_array.into_unknown())
+}
+
+fn throw_statement_busy_error(env: &Env) -> napi::Error {
+    let msg = "This statement is busy executing a query";
+    let err = napi::Error::new(napi::Status::InvalidArg, msg.to_string());
+    env.throw_type_error(msg, None).unwrap();
+    err
+}
+
+fn throw_statement_bound_error(env: &Env, msg: &str) -> napi::Error {
+    let err = napi::Error::new(napi::Status::InvalidArg, msg.to_string());
+    env.throw_type_error(msg, None).unwrap();
+    err
+}
+
 #[napi]
 pub struct Statement {
     stmt: Arc<tokio::sync::Mutex<libsql::Statement>>,
+    /// The raw statement underlying `stmt`, which is null for remote databases.
+    raw_stmt: RawStatement,
+    /// Whether the statement makes no direct changes to the database file.
+    readonly: bool,
+    /// The SQL text the statement was prepared from.
+    source: String,
     conn: Arc<tokio::sync::Mutex<libsql::Connection>>,
+    handle: *mut ffi::sqlite3,
+    tasks: Arc<TaskQueue>,
+    /// The names of the statement's named parameters, including their prefix.
+    parameters: Vec<String>,
+    /// Whether an iterator over the statement's rows is open, which keeps the
+    /// statement busy until it is exhausted or closed.
+    iterating: Arc<AtomicBool>,
+    /// The parameters permanently bound with `bind()`, used by every execution.
+    bound: RefCell<Option<libsql::params::Params>>,
     safe_ints: RefCell<bool>,
     raw: RefCell<bool>,
     pluck: RefCell<bool>,
 }
 
+/// An online backup in progress, which copies pages from a source database
+/// into a destination file.
+#[napi]
+pub struct Backup {
+    backup: *mut ffi::sqlite3_backup,
+    dest: *mut ffi::sqlite3,
+    /// Keeps the source connection open until the backup is closed.
+    conn: Option<Arc<tokio::sync::Mutex<libsql::Connection>>>,
+}
+
+#[napi(object)]
+pub struct BackupProgress {
+    pub totalPages: u32,
+    pub remainingPages: u32,
+}
+
+#[napi]
+impl Backup {
+    /// Copies up to `pages` pages to the destination, or all remaining pages
+    /// if `pages` is negative. Busy or locked source databases are retried on
+    /// the next transfer.
+    #[napi]
+    pub fn transfer(&self, env: Env, pages: i32) -> Result<BackupProgress> {
+        if self.conn.is_none() {
+            return Err(napi::Error::from_reason("The backup has been closed"));
+        }
+        let rc = unsafe { ffi::sqlite3_backup_step(self.backup, pages) };
+        match rc {
+            ffi::SQLITE_OK | ffi::SQLITE_DONE | ffi::SQLITE_BUSY | ffi::SQLITE_LOCKED => {}
+            _ => check_rc(self.dest, rc).map_err(|e| e.throw(&env))?,
+        }
+        let (total, remaining) = unsafe {
+            (
+                ffi::sqlite3_backup_pagecount(self.backup),
+                ffi::sqlite3_backup_remaining(self.backup),
+            )
+        };
+        Ok(BackupProgress {
+            totalPages: total as u32,
+            remainingPages: if rc == ffi::SQLITE_DONE {
+                0
+            } else {
+                remaining as u32
+            },
+        })
+    }
+
+    /// Finishes the backup and closes the destination database.
+    #[napi]
+    pub fn close(&mut self) -> Result<()> {
+        if self.conn.take().is_some() {
+            unsafe {
+                ffi::sqlite3_backup_finish(self.backup);
+                ffi::sqlite3_close(self.dest);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Backup {
+    fn drop(&mut self) {
+        self.close().ok();
+    }
+}
+
 #[napi(object)]
 pub struct RunResult {
     pub changes: f64,
     pub duration: f64,
-    pub lastInsertRowid: i64,
+    /// The rowid of the last inserted row, as a BigInt with safe integers.
+    #[napi(ts_type = "number | bigint")]
+    pub lastInsertRowid: JsUnknown,
+    /// The rows returned by a statement with a `RETURNING` clause. Other
+    /// statements that return data are stepped through without keeping them.
+    #[napi(ts_type = "unknown[]")]
+    pub rows: Option<JsUnknown>,
+}
+
+impl RunResult {
+    /// Converts an execution to the info object returned by `run()`.
+    fn new(
+        env: &Env,
+        execution: Execution,
+        safe_ints: bool,
+        raw: bool,
+        pluck: bool,
+    ) -> Result<Self> {
+        let Execution {
+            columns,
+            rows,
+            changes,
+            last_insert_rowid,
+            duration,
+        } = execution;
+        let rows = match rows {
+            Some(rows) => {
+                let mut js_array = env.create_array_with_length(rows.len())?;
+                for (idx, row) in rows.into_iter().enumerate() {
+                    let row = convert_row_values(env, &columns, row, safe_ints, raw, pluck)?;
+                    js_array.set_element(idx as u32, row)?;
+                }
+                Some(js_array.into_unknown())
+            }
+            None => None,
+        };
+        Ok(RunResult {
+            changes: changes as f64,
+            duration,
+            lastInsertRowid: convert_value(
+                env,
+                safe_ints,
+                libsql::Value::Integer(last_insert_rowid),
+            )?,
+            rows,
+        })
+    }
 }
 
 fn map_params(
-    stmt: &libsql::Statement,
+    parameters: &[String],
     params: Option<napi::JsUnknown>,
 ) -> Result<libsql::params::Params> {
     if let Some(params) = params {
@@ -410,7 +2670,7 @@ fn map_params(
                 if object.is_array()? {
                     map_params_array(object)
                 } else {
-                    map_params_object(stmt, object)
+                    map_params_object(parameters, object)
                 }
             }
             _ => map_params_single(params),
@@ -441,21 +2701,18 @@ fn map_params_array(object: napi::JsObject) -> Result<libsql::params::Params> {
 }
 
 fn map_params_object(
-    stmt: &libsql::Statement,
+    parameters: &[String],
     object: napi::JsObject,
 ) -> Result<libsql::params::Params> {
     let mut params = vec![];
 
-    for idx in 0..stmt.parameter_count() {
-        let name = stmt.parameter_name((idx + 1) as i32).unwrap();
-        let name = name.to_string();
-
+    for name in parameters {
         // Remove the leading ':' or '@' or '$' from parameter name
         let key = &name[1..];
 
         if let Ok(value) = object.get_named_property::<napi::JsUnknown>(key) {
             let value = map_value(value)?;
-            params.push((name, value));
+            params.push((name.clone(), value));
         }
     }
 
@@ -537,6 +2794,37 @@ fn map_value(value: JsUnknown) -> Result<libsql::Value> {
 
 #[napi]
 impl Statement {
+    fn new(
+        stmt: libsql::Statement,
+        raw_stmt: RawStatement,
+        source: String,
+        conn: Arc<tokio::sync::Mutex<libsql::Connection>>,
+        handle: *mut ffi::sqlite3,
+        tasks: Arc<TaskQueue>,
+        safe_ints: bool,
+    ) -> Statement {
+        let parameters = (1..=stmt.parameter_count())
+            .filter_map(|idx| stmt.parameter_name(idx as i32).map(str::to_string))
+            .collect();
+        let readonly =
+            raw_stmt.0.is_null() || unsafe { ffi::sqlite3_stmt_readonly(raw_stmt.0) } != 0;
+        Statement {
+            stmt: Arc::new(Mutex::new(stmt)),
+            raw_stmt,
+            readonly,
+            source,
+            conn,
+            handle,
+            tasks,
+            parameters,
+            iterating: Arc::new(AtomicBool::new(false)),
+            bound: RefCell::new(None),
+            safe_ints: RefCell::new(safe_ints),
+            raw: RefCell::new(false),
+            pluck: RefCell::new(false),
+        }
+    }
+
     #[napi]
     pub fn columns(&self, env: Env) -> Result<Array> {
         let rt = runtime()?;
@@ -576,83 +2864,128 @@ impl Statement {
     }
     #[napi]
     pub fn iterate(&self, env: Env, params: Option<napi::JsUnknown>) -> Result<napi::JsObject> {
+        self.check_not_busy(&env)?;
         let rt = runtime()?;
         // Get safe_ints and raw flags
         let safe_ints = *self.safe_ints.borrow();
         let raw = *self.raw.borrow();
         let stmt = self.stmt.clone();
+        let params = self.params(&env, params)?;
         // Lock statement and run query synchronously
-        let rows = rt.block_on(async {
-            let mut stmt = stmt.lock().await;
-            stmt.reset();
-            let params = if let Some(params) = params {
-                map_params(&stmt, Some(params)).unwrap()
-            } else {
-                libsql::params::Params::None
-            };
-            stmt.query(params).await.map_err(Error::from)
-        })?;
+        let rows = rt
+            .block_on(async {
+                let stmt = stmt.lock().await;
+                stmt.reset();
+                stmt.query(params).await
+            })
+            .map_err(|e| Error::from(e).throw(&env))?;
+        self.iterating.store(true, Ordering::SeqCst);
+        let rows = IteratorRows {
+            rows: Mutex::new(Some(rows)),
+            stmt: self.stmt.clone(),
+            iterating: self.iterating.clone(),
+        };
         // Wrap rows in an iterator struct
-        StatementRows::new(env, Arc::new(tokio::sync::Mutex::new(rows)), safe_ints, raw)
+        StatementRows::create(env, Arc::new(rows), self.tasks.clone(), safe_ints, raw)
     }
 
     #[napi]
-    pub fn run(&self, params: Option<napi::JsUnknown>) -> Result<RunResult> {
+    pub fn run(&self, env: Env, params: Option<napi::JsUnknown>) -> Result<RunResult> {
+        self.check_not_busy(&env)?;
         let rt = runtime()?;
-        rt.block_on(async move {
-            let conn = self.conn.lock().await;
-            let total_changes_before = conn.total_changes();
-            // Get start time
-            let start = std::time::Instant::now();
+        let params = self.params(&env, params)?;
+        let execution = rt
+            .block_on(execute(&self.conn, &self.stmt, params, self.returning()))
+            .map_err(|e| e.throw(&env))?;
+        RunResult::new(
+            &env,
+            execution,
+            *self.safe_ints.borrow(),
+            *self.raw.borrow(),
+            *self.pluck.borrow(),
+        )
+    }
 
-            let mut stmt = self.stmt.lock().await;
-            stmt.reset();
-            let params = if let Some(params) = params {
-                map_params(&stmt, Some(params))?
-            } else {
-                libsql::params::Params::None
-            };
-            stmt.query(params).await.map_err(Error::from)?;
-            let changes = if conn.total_changes() == total_changes_before {
-                0
-            } else {
-                conn.changes()
-            };
-            let last_insert_row_id = conn.last_insert_rowid();
-            // Calculate duration
-            let duration = start.elapsed().as_secs_f64();
+    /// Executes the statement off the JS thread, resolving to the same info
+    /// object as `run()`.
+    #[napi(ts_return_type = "Promise<RunResult>")]
+    pub fn runAsync(&self, env: Env, params: Option<napi::JsUnknown>) -> Result<napi::JsObject> {
+        self.check_not_busy(&env)?;
+        let task = RunTask {
+            conn: self.conn.clone(),
+            stmt: self.stmt.clone(),
+            params: self.params(&env, params)?,
+            returning: self.returning(),
+            safe_ints: *self.safe_ints.borrow(),
+            raw: *self.raw.borrow(),
+            pluck: *self.pluck.borrow(),
+        };
+        self.tasks.submit(&env, task)
+    }
 
-            Ok(RunResult {
-                changes: changes as f64,
-                duration,
-                lastInsertRowid: last_insert_row_id,
-            })
-        })
+    /// Whether `run()` returns the rows of the statement: readers that write
+    /// to the database, which are statements with a `RETURNING` clause. On
+    /// remote databases, where that is unknown, the rows of every reader are
+    /// returned.
+    fn returning(&self) -> bool {
+        self.handle.is_null() || !self.readonly
+    }
+
+    /// Queries the statement off the JS thread, resolving to the same
+    /// rows as `all()`.
+    #[napi(ts_return_type = "Promise<unknown>")]
+    pub fn allAsync(&self, env: Env, params: Option<napi::JsUnknown>) -> Result<napi::JsObject> {
+        self.query_async(env, params, false)
+    }
+
+    /// Queries the statement off the JS thread, resolving to the same
+    /// row as `get()`.
+    #[napi(ts_return_type = "Promise<unknown>")]
+    pub fn getAsync(&self, env: Env, params: Option<napi::JsUnknown>) -> Result<napi::JsObject> {
+        self.query_async(env, params, true)
+    }
+
+    fn query_async(
+        &self,
+        env: Env,
+        params: Option<napi::JsUnknown>,
+        first: bool,
+    ) -> Result<napi::JsObject> {
+        self.check_not_busy(&env)?;
+        let task = QueryTask {
+            stmt: self.stmt.clone(),
+            params: self.params(&env, params)?,
+            safe_ints: *self.safe_ints.borrow(),
+            raw: *self.raw.borrow(),
+            pluck: *self.pluck.borrow(),
+            first,
+        };
+        self.tasks.submit(&env, task)
     }
 
     #[napi]
     pub fn all(&self, env: Env, params: Option<napi::JsUnknown>) -> Result<Array> {
+        self.check_not_busy(&env)?;
         let rt = runtime()?;
         let safe_ints = *self.safe_ints.borrow();
         let raw = *self.raw.borrow();
+        let params = self.params(&env, params)?;
 
-        let mut rows = rt.block_on(async {
-            let mut stmt = self.stmt.lock().await;
-            stmt.reset();
-            let params = if let Some(params) = params {
-                map_params(&stmt, Some(params))?
-            } else {
-                libsql::params::Params::None
-            };
-            stmt.query(params)
-                .await
-                .map_err(|e| napi::Error::from_reason(e.to_string()))
-        })?;
+        let mut rows = rt
+            .block_on(async {
+                let stmt = self.stmt.lock().await;
+                stmt.reset();
+                stmt.query(params).await
+            })
+            .map_err(|e| Error::from(e).throw(&env))?;
 
         let mut js_array = env.create_array(0)?;
         let mut idx = 0u32;
         let pluck = *self.pluck.borrow();
-        while let Some(row) = rt.block_on(rows.next()).map_err(Error::from)? {
+        while let Some(row) = rt
+            .block_on(rows.next())
+            .map_err(|e| Error::from(e).throw(&env))?
+        {
             let js_value = if raw {
                 // Convert row to array
                 let js_array = convert_row_raw(&env, safe_ints, &rows, &row)?;
@@ -700,12 +3033,7 @@ impl Statement {
 
     #[napi]
     pub fn raw(&self, raw: Option<bool>) -> Result<&Self> {
-        let rt = runtime()?;
-        let returns_data = rt.block_on(async move {
-            let stmt = self.stmt.lock().await;
-            !stmt.columns().is_empty()
-        });
-        if !returns_data {
+        if !self.reader()? {
             return Err(napi::Error::from_reason(
                 "The raw() method is only for statements that return data",
             ));
@@ -716,6 +3044,7 @@ impl Statement {
 
     #[napi]
     pub fn get(&self, env: Env, params: Option<napi::JsUnknown>) -> Result<napi::JsUnknown> {
+        self.check_not_busy(&env)?;
         let rt = runtime()?;
 
         // Get start time
@@ -727,17 +3056,17 @@ impl Statement {
         // Get raw setting
         let raw = *self.raw.borrow();
 
+        let params = self.params(&env, params)?;
+
         // Execute the statement
         rt.block_on(async move {
-            let mut stmt = self.stmt.lock().await;
+            let stmt = self.stmt.lock().await;
             stmt.reset();
-            let params = if let Some(params) = params {
-                map_params(&stmt, Some(params))?
-            } else {
-                libsql::params::Params::None
-            };
-            let mut rows = stmt.query(params).await.map_err(Error::from)?;
-            let row = rows.next().await.map_err(Error::from)?;
+            let mut rows = stmt
+                .query(params)
+                .await
+                .map_err(|e| Error::from(e).throw(&env))?;
+            let row = rows.next().await.map_err(|e| Error::from(e).throw(&env))?;
             // Calculate duration
             let duration = start.elapsed().as_secs_f64();
             let result = match row {
@@ -773,57 +3102,303 @@ impl Statement {
         })
     }
 
+    /// The SQL text the statement was prepared from.
+    #[napi(getter)]
+    pub fn source(&self) -> String {
+        self.source.clone()
+    }
+
+    /// Whether the statement returns data, such as a `SELECT` or a statement
+    /// with a `RETURNING` clause.
+    #[napi(getter)]
+    pub fn reader(&self) -> Result<bool> {
+        let rt = runtime()?;
+        Ok(rt.block_on(async {
+            let stmt = self.stmt.lock().await;
+            stmt.column_count() > 0
+        }))
+    }
+
+    /// Whether the statement makes no direct changes to the database file.
+    #[napi(getter)]
+    pub fn readonly(&self) -> Result<bool> {
+        self.check_local()?;
+        Ok(self.readonly)
+    }
+
+    /// Returns the SQL text of the statement with its parameters replaced by
+    /// the bound parameters, or the given ones, as SQL literals.
+    #[napi]
+    pub fn expandedSql(&self, env: Env, params: Option<napi::JsUnknown>) -> Result<String> {
+        self.check_local()?;
+        self.check_not_busy(&env)?;
+        let params = self.params(&env, params)?;
+        if self.raw_stmt.0.is_null() {
+            return Ok(String::new());
+        }
+        let rt = runtime()?;
+        let stmt = rt.block_on(self.stmt.lock());
+        stmt.reset();
+        // Executions bind their own parameters, so those bound here are
+        // cleared once expanded.
+        let bound = self.raw_stmt.bind(self.handle, &params);
+        let sql = unsafe { ffi::sqlite3_expanded_sql(self.raw_stmt.0) };
+        unsafe { ffi::sqlite3_clear_bindings(self.raw_stmt.0) };
+        bound.map_err(|e| e.throw(&env))?;
+        unsafe {
+            if sql.is_null() {
+                return Err(Error(libsql::Error::SqliteFailure(
+                    ffi::SQLITE_NOMEM,
+                    "out of memory".to_string(),
+                ))
+                .throw(&env));
+            }
+            let expanded = std::ffi::CStr::from_ptr(sql).to_string_lossy().into_owned();
+            ffi::sqlite3_free(sql as *mut c_void);
+            Ok(expanded)
+        }
+    }
+
+    /// Permanently binds parameters to the statement, which are then used by
+    /// every execution and can no longer be overridden.
+    #[napi]
+    pub fn bind(&self, env: Env, params: Option<napi::JsUnknown>) -> Result<&Self> {
+        self.check_not_busy(&env)?;
+        if self.bound.borrow().is_some() {
+            return Err(throw_statement_bound_error(
+                &env,
+                "The bind() method can only be invoked once per statement object",
+            ));
+        }
+        let params = map_params(&self.parameters, params)?;
+        self.bound.replace(Some(params));
+        Ok(self)
+    }
+
     #[napi]
     pub fn safeIntegers(&self, toggle: Option<bool>) -> Result<&Self> {
         self.safe_ints.replace(toggle.unwrap_or(true));
         Ok(self)
     }
+
+    /// Aborts the queries running on the statement's connection, which fail
+    /// with `SQLITE_INTERRUPT`.
+    #[napi]
+    pub fn interrupt(&self, env: Env) -> Result<()> {
+        interrupt(&self.conn, self.handle, &self.tasks).map_err(|e| e.throw(&env))
+    }
+
+    /// Fails for statements of remote databases, which have no raw statement
+    /// to inspect.
+    fn check_local(&self) -> Result<()> {
+        if self.handle.is_null() {
+            return Err(napi::Error::from_reason(
+                "This operation is only supported on local databases",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the parameters of an execution, which must not be given once
+    /// parameters are bound to the statement.
+    fn params(&self, env: &Env, params: Option<napi::JsUnknown>) -> Result<libsql::params::Params> {
+        match &*self.bound.borrow() {
+            Some(_) if params.is_some() => Err(throw_statement_bound_error(
+                env,
+                "This statement already has bound parameters",
+            )),
+            Some(bound) => Ok(bound.clone()),
+            None => map_params(&self.parameters, params),
+        }
+    }
+
+    /// Fails while an iterator over the statement's rows is open, as executing
+    /// the statement again would invalidate it.
+    fn check_not_busy(&self, env: &Env) -> Result<()> {
+        if self.iterating.load(Ordering::SeqCst) {
+            return Err(throw_statement_busy_error(env));
+        }
+        Ok(())
+    }
 }
 
-#[napi]
-pub struct StatementRows {
-    rows: Arc<tokio::sync::Mutex<libsql::Rows>>,
-    safe_ints: bool,
-    raw: bool,
-    env: Env,
+/// The raw SQLite statement underlying a libSQL statement, which exposes what
+/// libSQL statements do not. libSQL owns and finalizes it.
+struct RawStatement(*mut ffi::sqlite3_stmt);
+
+// The statement is only used while holding the lock of its libSQL statement.
+unsafe impl Send for RawStatement {}
+
+impl RawStatement {
+    /// Returns the statement last prepared on the connection, which SQLite
+    /// lists first, if it was prepared from `source`. This must happen while
+    /// holding the connection lock the statement was prepared with.
+    fn last_prepared(handle: *mut ffi::sqlite3, source: &str) -> Self {
+        if handle.is_null() {
+            return RawStatement(std::ptr::null_mut());
+        }
+        unsafe {
+            let stmt = ffi::sqlite3_next_stmt(handle, std::ptr::null_mut());
+            let sql = if stmt.is_null() {
+                std::ptr::null()
+            } else {
+                ffi::sqlite3_sql(stmt)
+            };
+            if sql.is_null()
+                || !source
+                    .as_bytes()
+                    .starts_with(std::ffi::CStr::from_ptr(sql).to_bytes())
+            {
+                return RawStatement(std::ptr::null_mut());
+            }
+            RawStatement(stmt)
+        }
+    }
+
+    /// Binds parameters to the statement.
+    fn bind(
+        &self,
+        handle: *mut ffi::sqlite3,
+        params: &libsql::params::Params,
+    ) -> std::result::Result<(), Error> {
+        let values: Vec<(c_int, &libsql::Value)> = match params {
+            libsql::params::Params::None => vec![],
+            libsql::params::Params::Positional(values) => values
+                .iter()
+                .enumerate()
+                .map(|(idx, value)| (idx as c_int + 1, value))
+                .collect(),
+            libsql::params::Params::Named(values) => values
+                .iter()
+                .filter_map(|(name, value)| {
+                    let name = std::ffi::CString::new(name.as_str()).ok()?;
+                    let idx = unsafe { ffi::sqlite3_bind_parameter_index(self.0, name.as_ptr()) };
+                    (idx > 0).then_some((idx, value))
+                })
+                .collect(),
+        };
+        for (idx, value) in values {
+            let rc = unsafe {
+                match value {
+                    libsql::Value::Null => ffi::sqlite3_bind_null(self.0, idx),
+                    libsql::Value::Integer(value) => ffi::sqlite3_bind_int64(self.0, idx, *value),
+                    libsql::Value::Real(value) => ffi::sqlite3_bind_double(self.0, idx, *value),
+                    libsql::Value::Text(value) => ffi::sqlite3_bind_text(
+                        self.0,
+                        idx,
+                        value.as_ptr() as *const c_char,
+                        value.len() as c_int,
+                        ffi::SQLITE_TRANSIENT(),
+                    ),
+                    libsql::Value::Blob(value) => ffi::sqlite3_bind_blob(
+                        self.0,
+                        idx,
+                        value.as_ptr() as *const c_void,
+                        value.len() as c_int,
+                        ffi::SQLITE_TRANSIENT(),
+                    ),
+                }
+            };
+            check_rc(handle, rc)?;
+        }
+        Ok(())
+    }
 }
 
 #[napi]
+pub struct StatementRows {}
+
 impl StatementRows {
-    pub fn new(
+    /// Creates the iterator returned by `Statement.iterate()`, a plain object
+    /// whose methods share the rows being iterated.
+    fn create(
         env: Env,
-        rows: Arc<tokio::sync::Mutex<libsql::Rows>>,
+        rows: Arc<IteratorRows>,
+        tasks: Arc<TaskQueue>,
         safe_ints: bool,
         raw: bool,
     ) -> Result<napi::JsObject> {
         let mut js_obj = env.create_object()?;
+        let columns: Arc<[String]> = {
+            let rows = runtime()?.block_on(rows.rows.lock());
+            let rows = rows.as_ref().unwrap();
+            (0..rows.column_count())
+                .map(|idx| rows.column_name(idx).unwrap_or_default().to_string())
+                .collect()
+        };
+        // Fetches up to `size` rows off the JS thread, resolving to an
+        // array that is shorter than `size` once the rows are exhausted.
+        let batch_rows = rows.clone();
+        let next_batch_fn: JsFunction =
+            env.create_function_from_closure("nextBatch", move |ctx| {
+                let size = ctx.get::<napi::JsNumber>(0)?.get_uint32()? as usize;
+                let task = RowsTask {
+                    rows: batch_rows.clone(),
+                    columns: columns.clone(),
+                    size,
+                    safe_ints,
+                    raw,
+                };
+                tasks.submit(ctx.env, task)
+            })?;
+        js_obj.set_named_property("nextBatch", next_batch_fn)?;
+        let next_rows = rows.clone();
         let next_fn: JsFunction = env.create_function_from_closure("next", move |ctx| {
             let rt = runtime()?;
-            let rows = rows.clone();
+            let rows = next_rows.clone();
             rt.block_on(async move {
-                let mut rows = rows.lock().await;
-                let next_row = rows.next().await.map_err(Error::from)?;
+                let mut guard = rows.rows.lock().await;
+                let next_row = match guard.as_mut() {
+                    Some(rows) => rows.next().await,
+                    None => Ok(None),
+                };
                 let mut result_obj = ctx.env.create_object()?;
                 match next_row {
-                    Some(row) => {
+                    Ok(Some(row)) => {
+                        let rows = guard.as_ref().unwrap();
                         let value = if raw {
-                            convert_row_raw(&ctx.env, safe_ints, &rows, &row)?.into_unknown()
+                            convert_row_raw(ctx.env, safe_ints, rows, &row)?.into_unknown()
                         } else {
                             let mut js_object = ctx.env.create_object()?;
-                            convert_row(&ctx.env, safe_ints, &mut js_object, &rows, &row)?;
+                            convert_row(ctx.env, safe_ints, &mut js_object, rows, &row)?;
                             js_object.into_unknown()
                         };
                         result_obj.set_named_property("value", value)?;
                         result_obj.set_named_property("done", ctx.env.get_boolean(false)?)?;
                     }
-                    None => {
+                    Ok(None) => {
+                        drop(guard);
+                        rows.close().await;
                         result_obj.set_named_property("done", ctx.env.get_boolean(true)?)?;
                     }
+                    Err(e) => {
+                        drop(guard);
+                        rows.close().await;
+                        return Err(Error::from(e).throw(ctx.env));
+                    }
                 }
                 Ok(result_obj)
             })
         })?;
         js_obj.set_named_property("next", next_fn)?;
+        // Closing the iterator early, as `break` in a `for...of` loop does,
+        // releases the statement right away.
+        let return_rows = rows.clone();
+        let return_fn: JsFunction = env.create_function_from_closure("return", move |ctx| {
+            runtime()?.block_on(return_rows.close());
+            let mut result_obj = ctx.env.create_object()?;
+            if ctx.length > 0 {
+                result_obj.set_named_property("value", ctx.get::<JsUnknown>(0)?)?;
+            }
+            result_obj.set_named_property("done", ctx.env.get_boolean(true)?)?;
+            Ok(result_obj)
+        })?;
+        js_obj.set_named_property("return", return_fn)?;
+        let throw_fn: JsFunction = env.create_function_from_closure("throw", move |ctx| {
+            runtime()?.block_on(rows.close());
+            Err::<JsUnknown, _>(napi::Error::from(ctx.get::<JsUnknown>(0)?))
+        })?;
+        js_obj.set_named_property("throw", throw_fn)?;
         // Create iterator function
         let iterator_fn: JsFunction = env.create_function_from_closure("iterator", move |ctx| {
             Ok(ctx.this::<napi::JsObject>())
@@ -840,6 +3415,570 @@ impl StatementRows {
     }
 }
 
+/// The rows of an open iterator, which keep its statement busy until they are
+/// exhausted or the iterator is closed.
+pub struct IteratorRows {
+    rows: Mutex<Option<libsql::Rows>>,
+    stmt: Arc<tokio::sync::Mutex<libsql::Statement>>,
+    iterating: Arc<AtomicBool>,
+}
+
+impl IteratorRows {
+    /// Drops the rows and resets the statement, which can then be executed
+    /// again.
+    async fn close(&self) {
+        if self.rows.lock().await.take().is_some() {
+            self.stmt.lock().await.reset();
+            self.iterating.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+impl Drop for IteratorRows {
+    fn drop(&mut self) {
+        // An iterator that was never closed releases its statement once it
+        // is garbage collected.
+        if self.rows.get_mut().is_some() {
+            self.iterating.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// The outcome of running a statement, converted to JS values on the JS thread.
+pub struct Execution {
+    columns: Vec<String>,
+    /// The rows returned by the statement, if it returns data.
+    rows: Option<Vec<Vec<libsql::Value>>>,
+    changes: u64,
+    last_insert_rowid: i64,
+    duration: f64,
+}
+
+/// Runs a statement to completion. Statements that return data are stepped
+/// through all their rows, which are only collected when `collect` is set,
+/// and others are executed.
+async fn execute(
+    conn: &Mutex<libsql::Connection>,
+    stmt: &Mutex<libsql::Statement>,
+    params: libsql::params::Params,
+    collect: bool,
+) -> std::result::Result<Execution, SqliteError> {
+    let conn = conn.lock().await;
+    let total_changes_before = conn.total_changes();
+    // Get start time
+    let start = std::time::Instant::now();
+
+    let stmt = stmt.lock().await;
+    stmt.reset();
+    let (columns, rows) = if stmt.column_count() > 0 {
+        let mut rows = stmt.query(params).await.map_err(Error::from)?;
+        if !collect {
+            while rows.next().await.map_err(Error::from)?.is_some() {}
+            (vec![], None)
+        } else {
+            let columns = (0..rows.column_count())
+                .map(|idx| rows.column_name(idx).unwrap_or_default().to_string())
+                .collect();
+            let mut values = vec![];
+            while let Some(row) = rows.next().await.map_err(Error::from)? {
+                values.push(row_values(&rows, &row)?);
+            }
+            (columns, Some(values))
+        }
+    } else {
+        stmt.execute(params).await.map_err(Error::from)?;
+        (vec![], None)
+    };
+    stmt.reset();
+    // Statements that change nothing, such as DDL, leave the count of the
+    // last INSERT, UPDATE or DELETE behind.
+    let changes = if conn.total_changes() == total_changes_before {
+        0
+    } else {
+        conn.changes()
+    };
+    let last_insert_row_id = conn.last_insert_rowid();
+    // Calculate duration
+    let duration = start.elapsed().as_secs_f64();
+
+    Ok(Execution {
+        columns,
+        rows,
+        changes,
+        last_insert_rowid: last_insert_row_id,
+        duration,
+    })
+}
+
+thread_local! {
+    /// Whether the thread is running an async task, whose queries must not
+    /// call back into JS.
+    static IN_ASYNC_TASK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A task run by the task queue of a connection, which computes its output
+/// off the JS thread and converts it to a JS value back on the JS thread.
+trait QueueTask: Send + 'static {
+    type Output: Send + 'static;
+    type JsValue: ToNapiValue;
+
+    async fn compute(&mut self, ticket: &Ticket) -> std::result::Result<Self::Output, SqliteError>;
+
+    fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue>;
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Runs the async tasks of a connection one at a time, in the order they
+/// were submitted. Queued tasks wait in a channel served by a worker on the
+/// runtime, and only take a thread of its blocking pool while they run.
+struct TaskQueue {
+    jobs: tokio::sync::mpsc::UnboundedSender<Job>,
+    /// The number of times the connection was interrupted, which aborts the
+    /// tasks submitted before then.
+    interrupts: Arc<AtomicU64>,
+    /// The number of submitted tasks that have not finished yet, while which
+    /// JS authorizers, which tasks cannot call, are rejected.
+    pending: Arc<AtomicU64>,
+    /// Whether the connection has a JS authorizer, which rejects tasks.
+    js_authorizer: AtomicBool,
+}
+
+impl TaskQueue {
+    fn new() -> Result<Self> {
+        let (jobs, mut receiver) = tokio::sync::mpsc::unbounded_channel::<Job>();
+        runtime()?.spawn(async move {
+            while let Some(job) = receiver.recv().await {
+                tokio::task::spawn_blocking(job).await.ok();
+            }
+        });
+        Ok(TaskQueue {
+            jobs,
+            interrupts: Arc::new(AtomicU64::new(0)),
+            pending: Arc::new(AtomicU64::new(0)),
+            js_authorizer: AtomicBool::new(false),
+        })
+    }
+
+    /// Queues a task, returning a promise that settles with its result. This
+    /// must happen on the JS thread when the task is submitted.
+    fn submit<T: QueueTask>(&self, env: &Env, mut task: T) -> Result<napi::JsObject> {
+        if self.js_authorizer.load(Ordering::SeqCst) {
+            return Err(throw_async_authorizer_error(env));
+        }
+        let rt = runtime()?;
+        let (deferred, promise) = env.create_deferred()?;
+        let ticket = Ticket {
+            interrupts: self.interrupts.clone(),
+            submitted_at: self.interrupts.load(Ordering::SeqCst),
+        };
+        let pending = self.pending.clone();
+        let job = Box::new(move || {
+            IN_ASYNC_TASK.with(|in_task| in_task.set(true));
+            let output = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                ticket.check_interrupt()?;
+                rt.block_on(task.compute(&ticket))
+            }))
+            .unwrap_or_else(|_| {
+                Err(SqliteError::from_code(
+                    "The asynchronous task panicked".to_string(),
+                    "LIBSQL_ERROR",
+                    ffi::SQLITE_ERROR,
+                ))
+            });
+            IN_ASYNC_TASK.with(|in_task| in_task.set(false));
+            pending.fetch_sub(1, Ordering::SeqCst);
+            deferred.resolve(move |env| match output {
+                Ok(output) => task.resolve(env, output),
+                Err(error) => Err(error.throw(&env)),
+            });
+        });
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        if self.jobs.send(job).is_err() {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+            return Err(napi::Error::from_reason(
+                "The task queue of the connection is closed",
+            ));
+        }
+        Ok(promise)
+    }
+}
+
+/// The interrupts a task is subject to.
+struct Ticket {
+    interrupts: Arc<AtomicU64>,
+    /// The number of interrupts when the task was submitted.
+    submitted_at: u64,
+}
+
+impl Ticket {
+    /// Fails with `SQLITE_INTERRUPT` if the connection was interrupted since
+    /// the task was submitted.
+    fn check_interrupt(&self) -> std::result::Result<(), SqliteError> {
+        if self.interrupts.load(Ordering::SeqCst) == self.submitted_at {
+            return Ok(());
+        }
+        let err = libsql::Error::SqliteFailure(ffi::SQLITE_INTERRUPT, "interrupted".to_string());
+        Err(Error(err).into())
+    }
+}
+
+/// Leaves the error of a JS callback pending as an exception, unless one
+/// already is, so that the statement calling it throws the original error
+/// once SQLite returns. Returns the message to report to SQLite.
+unsafe fn callback_error(env: napi::sys::napi_env, err: napi::Error) -> String {
+    let message = err.reason.clone();
+    if IN_ASYNC_TASK.with(Cell::get) {
+        return message;
+    }
+    let mut pending = false;
+    napi::sys::napi_is_exception_pending(env, &mut pending);
+    // Converting the error also releases the reference it holds to a thrown
+    // JS value.
+    let error = napi::JsError::from(err).into_value(env);
+    if !pending {
+        napi::sys::napi_throw(env, error);
+    }
+    message
+}
+
+/// Returns the environment to call back into JS from a query, which is not
+/// possible for queries running in an async task.
+unsafe fn callback_env(env: napi::sys::napi_env) -> Result<Env> {
+    if IN_ASYNC_TASK.with(Cell::get) {
+        return Err(napi::Error::from_reason(
+            "JavaScript functions cannot be called by asynchronous queries",
+        ));
+    }
+    Ok(Env::from_raw(env))
+}
+
+pub struct PrepareTask {
+    conn: Arc<tokio::sync::Mutex<libsql::Connection>>,
+    sql: String,
+    handle: *mut ffi::sqlite3,
+    tasks: Arc<TaskQueue>,
+    safe_ints: bool,
+    /// Whether the connection is authorized by an authorizer policy.
+    policy: bool,
+}
+
+// The raw handle is only passed on to the statement on the JS thread.
+unsafe impl Send for PrepareTask {}
+
+impl QueueTask for PrepareTask {
+    type Output = (libsql::Statement, RawStatement);
+    type JsValue = Statement;
+
+    async fn compute(
+        &mut self,
+        _ticket: &Ticket,
+    ) -> std::result::Result<Self::Output, SqliteError> {
+        let conn = self.conn.lock().await;
+        let stmt = conn.prepare(&self.sql).await.map_err(|e| {
+            Error::from(e)
+                .with_offset(self.handle)
+                .denied_by(self.policy)
+        })?;
+        Ok((stmt, RawStatement::last_prepared(self.handle, &self.sql)))
+    }
+
+    fn resolve(&mut self, _env: Env, (stmt, raw_stmt): Self::Output) -> Result<Self::JsValue> {
+        Ok(Statement::new(
+            stmt,
+            raw_stmt,
+            std::mem::take(&mut self.sql),
+            self.conn.clone(),
+            self.handle,
+            self.tasks.clone(),
+            self.safe_ints,
+        ))
+    }
+}
+
+pub struct ExecTask {
+    conn: Arc<tokio::sync::Mutex<libsql::Connection>>,
+    sql: String,
+    /// Whether the connection is authorized by an authorizer policy.
+    policy: bool,
+}
+
+impl QueueTask for ExecTask {
+    type Output = ();
+    type JsValue = ();
+
+    async fn compute(
+        &mut self,
+        _ticket: &Ticket,
+    ) -> std::result::Result<Self::Output, SqliteError> {
+        let conn = self.conn.lock().await;
+        conn.execute_batch(&self.sql)
+            .await
+            .map_err(|e| SqliteError::from(Error::from(e)).denied_by(self.policy))?;
+        Ok(())
+    }
+
+    fn resolve(&mut self, _env: Env, _output: Self::Output) -> Result<Self::JsValue> {
+        Ok(())
+    }
+}
+
+pub struct PragmaTask {
+    conn: Arc<tokio::sync::Mutex<libsql::Connection>>,
+    sql: String,
+    safe_ints: bool,
+    /// Whether to only return the first value of the first row.
+    simple: bool,
+    /// Whether the connection is authorized by an authorizer policy.
+    policy: bool,
+}
+
+impl QueueTask for PragmaTask {
+    type Output = (Vec<String>, Vec<Vec<libsql::Value>>);
+    type JsValue = JsUnknown;
+
+    async fn compute(
+        &mut self,
+        _ticket: &Ticket,
+    ) -> std::result::Result<Self::Output, SqliteError> {
+        pragma_rows(&self.conn, &self.sql)
+            .await
+            .map_err(|e| SqliteError::from(e).denied_by(self.policy))
+    }
+
+    fn resolve(&mut self, env: Env, (columns, rows): Self::Output) -> Result<Self::JsValue> {
+        pragma_result(&env, columns, rows, self.safe_ints, self.simple)
+    }
+}
+
+pub struct SyncTask {
+    db: Arc<libsql::Database>,
+    /// The replication index to sync until, if any.
+    until: Option<u64>,
+}
+
+impl QueueTask for SyncTask {
+    type Output = SyncResult;
+    type JsValue = SyncResult;
+
+    async fn compute(
+        &mut self,
+        _ticket: &Ticket,
+    ) -> std::result::Result<Self::Output, SqliteError> {
+        let replicated = match self.until {
+            Some(replication_index) => self.db.sync_until(replication_index).await,
+            None => self.db.sync().await,
+        };
+        Ok(replicated.map_err(Error::from)?.into())
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+pub struct RunTask {
+    conn: Arc<tokio::sync::Mutex<libsql::Connection>>,
+    stmt: Arc<tokio::sync::Mutex<libsql::Statement>>,
+    params: libsql::params::Params,
+    returning: bool,
+    safe_ints: bool,
+    raw: bool,
+    pluck: bool,
+}
+
+impl QueueTask for RunTask {
+    type Output = Execution;
+    type JsValue = RunResult;
+
+    async fn compute(
+        &mut self,
+        _ticket: &Ticket,
+    ) -> std::result::Result<Self::Output, SqliteError> {
+        let params = std::mem::replace(&mut self.params, libsql::params::Params::None);
+        execute(&self.conn, &self.stmt, params, self.returning).await
+    }
+
+    fn resolve(&mut self, env: Env, execution: Self::Output) -> Result<Self::JsValue> {
+        RunResult::new(&env, execution, self.safe_ints, self.raw, self.pluck)
+    }
+}
+
+pub struct QueryTask {
+    stmt: Arc<tokio::sync::Mutex<libsql::Statement>>,
+    params: libsql::params::Params,
+    safe_ints: bool,
+    raw: bool,
+    pluck: bool,
+    /// Whether to only fetch the first row, as `get()` does.
+    first: bool,
+}
+
+/// The rows fetched by a `QueryTask`, converted to JS values on the JS thread.
+pub struct QueryRows {
+    columns: Vec<String>,
+    rows: Vec<Vec<libsql::Value>>,
+    duration: f64,
+}
+
+impl QueueTask for QueryTask {
+    type Output = QueryRows;
+    type JsValue = JsUnknown;
+
+    async fn compute(&mut self, ticket: &Ticket) -> std::result::Result<Self::Output, SqliteError> {
+        let params = std::mem::replace(&mut self.params, libsql::params::Params::None);
+        let start = std::time::Instant::now();
+        let stmt = self.stmt.lock().await;
+        stmt.reset();
+        let mut rows = stmt.query(params).await.map_err(Error::from)?;
+        let columns = (0..rows.column_count())
+            .map(|idx| rows.column_name(idx).unwrap_or_default().to_string())
+            .collect();
+        let mut values = vec![];
+        while let Some(row) = rows.next().await.map_err(Error::from)? {
+            values.push(row_values(&rows, &row)?);
+            if self.first {
+                break;
+            }
+            ticket.check_interrupt()?;
+        }
+        if self.first {
+            drop(rows);
+            stmt.reset();
+        }
+        Ok(QueryRows {
+            columns,
+            rows: values,
+            duration: start.elapsed().as_secs_f64(),
+        })
+    }
+
+    fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        let QueryRows {
+            columns,
+            rows,
+            duration,
+        } = output;
+        if self.first {
+            let Some(row) = rows.into_iter().next() else {
+                return Ok(env.get_undefined()?.into_unknown());
+            };
+            let row =
+                convert_row_values(&env, &columns, row, self.safe_ints, self.raw, self.pluck)?;
+            if self.raw || self.pluck {
+                return Ok(row);
+            }
+            // Add metadata
+            let mut row = row.coerce_to_object()?;
+            let mut metadata = env.create_object()?;
+            metadata.set_named_property("duration", env.create_double(duration)?)?;
+            row.set_named_property("_metadata", metadata)?;
+            return Ok(row.into_unknown());
+        }
+        let mut js_array = env.create_array_with_length(rows.len())?;
+        for (idx, row) in rows.into_iter().enumerate() {
+            let row =
+                convert_row_values(&env, &columns, row, self.safe_ints, self.raw, self.pluck)?;
+            js_array.set_element(idx as u32, row)?;
+        }
+        Ok(js_array.into_unknown())
+    }
+}
+
+pub struct RowsTask {
+    rows: Arc<IteratorRows>,
+    columns: Arc<[String]>,
+    size: usize,
+    safe_ints: bool,
+    raw: bool,
+}
+
+impl QueueTask for RowsTask {
+    type Output = Vec<Vec<libsql::Value>>;
+    type JsValue = napi::JsObject;
+
+    async fn compute(&mut self, ticket: &Ticket) -> std::result::Result<Self::Output, SqliteError> {
+        let batch = self.fetch(ticket).await;
+        if !matches!(&batch, Ok(batch) if batch.len() == self.size) {
+            self.rows.close().await;
+        }
+        batch
+    }
+
+    fn resolve(&mut self, env: Env, batch: Self::Output) -> Result<Self::JsValue> {
+        let mut js_array = env.create_array_with_length(batch.len())?;
+        for (idx, row) in batch.into_iter().enumerate() {
+            let row =
+                convert_row_values(&env, &self.columns, row, self.safe_ints, self.raw, false)?;
+            js_array.set_element(idx as u32, row)?;
+        }
+        Ok(js_array)
+    }
+}
+
+impl RowsTask {
+    async fn fetch(
+        &self,
+        ticket: &Ticket,
+    ) -> std::result::Result<Vec<Vec<libsql::Value>>, SqliteError> {
+        let mut rows = self.rows.rows.lock().await;
+        let Some(rows) = rows.as_mut() else {
+            return Ok(vec![]);
+        };
+        let mut batch = Vec::with_capacity(self.size);
+        while batch.len() < self.size {
+            let Some(row) = rows.next().await.map_err(Error::from)? else {
+                break;
+            };
+            batch.push(row_values(rows, &row)?);
+            ticket.check_interrupt()?;
+        }
+        Ok(batch)
+    }
+}
+
+/// Reads the values of a row, so that they can be converted to JS values
+/// after leaving the thread that fetched them.
+fn row_values(
+    rows: &libsql::Rows,
+    row: &libsql::Row,
+) -> std::result::Result<Vec<libsql::Value>, Error> {
+    let values = (0..rows.column_count())
+        .map(|idx| row.get_value(idx))
+        .collect::<libsql::Result<Vec<_>>>()?;
+    Ok(values)
+}
+
+/// Converts a row to an array in raw mode, to its first value in pluck mode
+/// and to an object keyed by column name otherwise.
+fn convert_row_values(
+    env: &Env,
+    columns: &[String],
+    row: Vec<libsql::Value>,
+    safe_ints: bool,
+    raw: bool,
+    pluck: bool,
+) -> Result<JsUnknown> {
+    if pluck {
+        return match row.into_iter().next() {
+            Some(value) => convert_value(env, safe_ints, value),
+            None => Ok(env.get_undefined()?.into_unknown()),
+        };
+    }
+    if raw {
+        let mut js_array = env.create_array_with_length(row.len())?;
+        for (idx, value) in row.into_iter().enumerate() {
+            js_array.set_element(idx as u32, convert_value(env, safe_ints, value)?)?;
+        }
+        return Ok(js_array.into_unknown());
+    }
+    let mut js_object = env.create_object()?;
+    for (column, value) in columns.iter().zip(row) {
+        js_object.set_named_property(column, convert_value(env, safe_ints, value)?)?;
+    }
+    Ok(js_object.into_unknown())
+}
+
 fn runtime() -> Result<&'static Runtime> {
     static RUNTIME: OnceCell<Runtime> = OnceCell::new();
 
@@ -910,24 +4049,26 @@ fn convert_row_raw(
             Err(e) => return Err(napi::Error::from_reason(e.to_string())),
         };
 
-        // Create appropriate JS value based on SQLite value type
-        let js_value = match value {
-            libsql::Value::Null => Ok(env.get_null()?.into_unknown()),
-            libsql::Value::Integer(v) => {
-                if safe_ints {
-                    Ok(env.create_bigint_from_i64(v)?.into_unknown()?)
-                } else {
-                    Ok(env.create_double(v as f64)?.into_unknown())
-                }
-            }
-            libsql::Value::Real(v) => Ok(env.create_double(v)?.into_unknown()),
-            libsql::Value::Text(v) => Ok(env.create_string(&v)?.into_unknown()),
-            libsql::Value::Blob(v) => env
-                .create_buffer_with_data(v.clone())
-                .map(|b| b.into_unknown()),
-        }?;
+        let js_value = convert_value(env, safe_ints, value)?;
 
         js_array.set(idx as u32, js_value)?;
     }
     Ok(js_array.coerce_to_object()?.into_unknown())
 }
+
+/// Maps a libSQL value to the corresponding JavaScript value.
+fn convert_value(env: &Env, safe_ints: bool, value: libsql::Value) -> Result<JsUnknown> {
+    match value {
+        libsql::Value::Null => Ok(env.get_null()?.into_unknown()),
+        libsql::Value::Integer(v) => {
+            if safe_ints {
+                env.create_bigint_from_i64(v)?.into_unknown()
+            } else {
+                Ok(env.create_double(v as f64)?.into_unknown())
+            }
+        }
+        libsql::Value::Real(v) => Ok(env.create_double(v)?.into_unknown()),
+        libsql::Value::Text(v) => Ok(env.create_string(&v)?.into_unknown()),
+        libsql::Value::Blob(v) => env.create_buffer_with_data(v).map(|b| b.into_unknown()),
+    }
+}
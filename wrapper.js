@@ -1,27 +1,200 @@
 "use strict";
 
-const { Database: NativeDb } = require("./index.js");
+const fs = require("fs");
+const path = require("path");
+const { Database: NativeDb, OpenFlags } = require("./index.js");
 const SqliteError = require("./sqlite-error.js");
 
-function convertError(err) {
-  // Handle errors from Rust with JSON-encoded message
-  if (typeof err.message === 'string') {
-    try {
-      const data = JSON.parse(err.message);
-      if (data && data.libsqlError) {
-        if (data.code === "SQLITE_AUTH") {
-          // For SQLITE_AUTH, preserve the JSON string for the test
-          return new SqliteError(err.message, data.code, data.rawCode);
-        } else {
-          // For all other errors, use the plain message string
-          return new SqliteError(data.message, data.code, data.rawCode);
+function getFunctionOption(options, key, required) {
+  const value = key in options ? options[key] : null;
+  if (typeof value === 'function') return value;
+  if (value != null) throw new TypeError(`Expected the "${key}" option to be a function`);
+  if (required) throw new TypeError(`Missing required option "${key}"`);
+  return null;
+}
+
+function runBackup(backup, handler, newFilename) {
+  let rate = 0;
+  let useDefault = true;
+  return new Promise((resolve, reject) => {
+    setImmediate(function step() {
+      try {
+        const progress = backup.transfer(rate);
+        if (!progress.remainingPages) {
+          backup.close();
+          resolve(progress);
+          return;
         }
+        if (useDefault) {
+          useDefault = false;
+          rate = 100;
+        }
+        if (handler) {
+          const ret = handler(progress);
+          if (ret !== undefined) {
+            if (typeof ret === 'number' && ret === ret) rate = Math.max(0, Math.min(0x7fffffff, Math.round(ret)));
+            else throw new TypeError('Expected progress callback to return a number or undefined');
+          }
+        }
+        setImmediate(step);
+      } catch (err) {
+        backup.close();
+        if (newFilename) fs.rmSync(newFilename, { force: true });
+        reject(err);
       }
-    } catch (_) {
-      // Not JSON, ignore
+    });
+  });
+}
+
+/**
+ * The number of rows fetched off the main thread at a time when iterating
+ * asynchronously over the rows of a statement.
+ */
+const ITERATE_BATCH_SIZE = 100;
+
+async function* iterateAsync(iterator, batchSize) {
+  try {
+    for (;;) {
+      const batch = await iterator.nextBatch(batchSize);
+      yield* batch;
+      if (batch.length < batchSize) return;
     }
+  } finally {
+    iterator.return();
+  }
+}
+
+const GeneratorFunctionPrototype = Object.getPrototypeOf(function* () {});
+
+function wrapTableFactory(factory) {
+  return function virtualTableFactory(moduleName, databaseName, tableName, ...args) {
+    const thisObject = { module: moduleName, database: databaseName, table: tableName };
+    const definition = factory.apply(thisObject, args);
+    if (typeof definition !== 'object' || definition === null) {
+      throw new TypeError(`Virtual table module "${moduleName}" did not return a table definition object`);
+    }
+    return parseTableDefinition(definition, 'returned', moduleName);
+  };
+}
+
+function parseTableDefinition(definition, verb, moduleName) {
+  if (!Object.prototype.hasOwnProperty.call(definition, 'rows')) {
+    throw new TypeError(`Virtual table module "${moduleName}" ${verb} a table definition without a "rows" property`);
+  }
+  if (!Object.prototype.hasOwnProperty.call(definition, 'columns')) {
+    throw new TypeError(`Virtual table module "${moduleName}" ${verb} a table definition without a "columns" property`);
+  }
+  const rows = definition.rows;
+  if (typeof rows !== 'function' || Object.getPrototypeOf(rows) !== GeneratorFunctionPrototype) {
+    throw new TypeError(`Virtual table module "${moduleName}" ${verb} a table definition with an invalid "rows" property (should be a generator function)`);
+  }
+  let columns = definition.columns;
+  if (!Array.isArray(columns) || !(columns = [...columns]).every(x => typeof x === 'string')) {
+    throw new TypeError(`Virtual table module "${moduleName}" ${verb} a table definition with an invalid "columns" property (should be an array of strings)`);
+  }
+  if (columns.length !== new Set(columns).size) {
+    throw new TypeError(`Virtual table module "${moduleName}" ${verb} a table definition with duplicate column names`);
+  }
+  if (!columns.length) {
+    throw new RangeError(`Virtual table module "${moduleName}" ${verb} a table definition with zero columns`);
+  }
+  let parameters;
+  if (Object.prototype.hasOwnProperty.call(definition, 'parameters')) {
+    parameters = definition.parameters;
+    if (!Array.isArray(parameters) || !(parameters = [...parameters]).every(x => typeof x === 'string')) {
+      throw new TypeError(`Virtual table module "${moduleName}" ${verb} a table definition with an invalid "parameters" property (should be an array of strings)`);
+    }
+  } else {
+    parameters = inferParameters(rows);
+  }
+  if (parameters.length !== new Set(parameters).size) {
+    throw new TypeError(`Virtual table module "${moduleName}" ${verb} a table definition with duplicate parameter names`);
+  }
+  if (parameters.length > 32) {
+    throw new RangeError(`Virtual table module "${moduleName}" ${verb} a table definition with more than the maximum number of 32 parameters`);
+  }
+  for (const parameter of parameters) {
+    if (columns.includes(parameter)) {
+      throw new TypeError(`Virtual table module "${moduleName}" ${verb} a table definition with column "${parameter}" which was ambiguously defined as both a column and parameter`);
+    }
+  }
+  let safeIntegers;
+  if (Object.prototype.hasOwnProperty.call(definition, 'safeIntegers')) {
+    safeIntegers = definition.safeIntegers;
+    if (typeof safeIntegers !== 'boolean') {
+      throw new TypeError(`Virtual table module "${moduleName}" ${verb} a table definition with an invalid "safeIntegers" property (should be a boolean)`);
+    }
+  }
+  let directOnly = false;
+  if (Object.prototype.hasOwnProperty.call(definition, 'directOnly')) {
+    directOnly = definition.directOnly;
+    if (typeof directOnly !== 'boolean') {
+      throw new TypeError(`Virtual table module "${moduleName}" ${verb} a table definition with an invalid "directOnly" property (should be a boolean)`);
+    }
+  }
+  const columnDefinitions = [
+    ...parameters.map(identifier).map(str => `${str} HIDDEN`),
+    ...columns.map(identifier),
+  ];
+  return {
+    sql: `CREATE TABLE x(${columnDefinitions.join(', ')});`,
+    rows: wrapTableRows(rows, new Map(columns.map((x, i) => [x, parameters.length + i])), moduleName),
+    parameterCount: parameters.length,
+    safeIntegers,
+    directOnly,
+  };
+}
+
+// Yields every row as an array of the parameter values followed by the column values.
+function wrapTableRows(generator, columnMap, moduleName) {
+  return function* virtualTable(...args) {
+    const output = args.map(x => Buffer.isBuffer(x) ? Buffer.from(x) : x);
+    for (let i = 0; i < columnMap.size; ++i) {
+      output.push(null);
+    }
+    for (const row of generator(...args)) {
+      if (Array.isArray(row)) {
+        if (row.length !== columnMap.size) {
+          throw new TypeError(`Virtual table module "${moduleName}" yielded a row with an incorrect number of columns`);
+        }
+        const offset = output.length - columnMap.size;
+        for (let i = 0; i < columnMap.size; ++i) {
+          output[i + offset] = row[i];
+        }
+      } else if (typeof row === 'object' && row !== null) {
+        let count = 0;
+        for (const key of Object.keys(row)) {
+          const index = columnMap.get(key);
+          if (index === undefined) {
+            throw new TypeError(`Virtual table module "${moduleName}" yielded a row with an undeclared column "${key}"`);
+          }
+          output[index] = row[key];
+          count += 1;
+        }
+        if (count !== columnMap.size) {
+          throw new TypeError(`Virtual table module "${moduleName}" yielded a row with missing columns`);
+        }
+      } else {
+        throw new TypeError(`Virtual table module "${moduleName}" yielded something that isn't a valid row object`);
+      }
+      yield output;
+    }
+  };
+}
+
+function inferParameters({ length }) {
+  if (!Number.isInteger(length) || length < 0) {
+    throw new TypeError('Expected function.length to be a positive integer');
+  }
+  const params = [];
+  for (let i = 0; i < length; ++i) {
+    params.push(`$${i + 1}`);
   }
-  return err;
+  return params;
+}
+
+function identifier(str) {
+  return `"${str.replace(/"/g, '""')}"`;
 }
 
 /**
@@ -29,13 +202,41 @@ function convertError(err) {
  */
 class Database {
   /**
-   * Creates a new database connection. If the database file pointed to by `path` does not exists, it will be created.
+   * Creates a new database connection. If the database file pointed to by `path` does not exists, it will be created,
+   * unless `opts.fileMustExist` is set. Paths starting with `file:` are interpreted as SQLite URIs.
+   * If `path` is a buffer returned by `serialize()`, the database is opened in memory from its contents.
    *
    * @constructor
-   * @param {string} path - Path to the database file.
+   * @param {string|Buffer} path - Path to the database file, or a serialized database.
+   * @param {object} [opts] - The options for the connection.
+   * @param {boolean} [opts.readonly] - Open the database as read-only.
+   * @param {boolean} [opts.fileMustExist] - Throw instead of creating a missing database file.
+   * @param {number} [opts.flags] - The `OpenFlags` to open the database file with, replacing those derived from `readonly` and `fileMustExist`.
+   * @param {function} [opts.verbose] - Called asynchronously with the SQL of each statement run, with its parameters expanded.
+   * @param {string|Buffer} [opts.encryptionKey] - The key to encrypt the database file with.
+   * @param {string} [opts.encryptionCipher] - The cipher used with the encryption key.
    */
   constructor(path, opts) {
-    this.db = new NativeDb(path, opts);
+    const encryptionKey = opts ? opts.encryptionKey : undefined;
+    if (encryptionKey !== undefined && typeof encryptionKey !== 'string' && !Buffer.isBuffer(encryptionKey)) {
+      throw new TypeError('Expected the "encryptionKey" option to be a string or a Buffer');
+    }
+    const verbose = opts ? opts.verbose : undefined;
+    if (verbose != null && typeof verbose !== 'function') {
+      throw new TypeError('Expected the "verbose" option to be a function');
+    }
+    if (Buffer.isBuffer(path)) {
+      const buffer = path;
+      this.db = new NativeDb(":memory:", opts);
+      try {
+        this.db.deserialize(buffer, "main", Boolean(opts && opts.readonly));
+      } catch (err) {
+        this.db.close();
+        throw err;
+      }
+    } else {
+      this.db = new NativeDb(path, opts);
+    }
     this.memory = this.db.memory
     const db = this.db;
     Object.defineProperties(this, {
@@ -44,15 +245,34 @@ class Database {
           return db.inTransaction();
         }
       },
+      name: {
+        get() {
+          return db.name;
+        }
+      },
+      readonly: {
+        get() {
+          return db.readonly;
+        }
+      },
     });
   }
 
+  /**
+   * Syncs an embedded replica with its primary database.
+   */
   sync() {
-    throw new Error("not implemented");
+    return this.db.sync();
   }
 
+  /**
+   * Syncs an embedded replica until it reaches the given replication index.
+   *
+   * @param {number} replicationIndex - The replication index to wait for.
+   */
   syncUntil(replicationIndex) {
-    throw new Error("not implemented");
+    if (typeof replicationIndex !== 'number') throw new TypeError('Expected first argument to be a number');
+    return this.db.syncUntil(replicationIndex);
   }
 
   /**
@@ -61,11 +281,7 @@ class Database {
    * @param {string} sql - The SQL statement string to prepare.
    */
   prepare(sql) {
-    try {
-      return new Statement(this.db.prepare(sql));
-    } catch (err) {
-      throw convertError(err);
-    }
+    return new Statement(this.db.prepare(sql));
   }
 
   /**
@@ -105,41 +321,177 @@ class Database {
     return properties.default.value;
   }
 
+  /**
+   * Executes a pragma and returns its rows.
+   *
+   * @param {string} source - The pragma, optionally schema-qualified and followed by an argument, such as "main.journal_mode = WAL".
+   * @param {object} [options] - The options for the pragma.
+   * @param {boolean} [options.simple] - Returns only the first value of the first row.
+   */
   pragma(source, options) {
     if (options == null) options = {};
     if (typeof source !== 'string') throw new TypeError('Expected first argument to be a string');
     if (typeof options !== 'object') throw new TypeError('Expected second argument to be an options object');
-    const simple = options['simple'];
-    const stmt = this.prepare(`PRAGMA ${source}`, this, true);
-    return simple ? stmt.pluck().get() : stmt.all();
+    return this.db.pragma(source, Boolean(options.simple));
   }
 
-  backup(filename, options) {
-    throw new Error("not implemented");
+  /**
+   * Backs up the database into a file without blocking writers.
+   *
+   * @param {string} filename - The path of the backup file.
+   * @param {object} [options] - The options for the backup.
+   * @param {string} [options.attached] - The name of the attached database to back up.
+   * @param {function} [options.progress] - Called with `{ totalPages, remainingPages }` after each step; may return the number of pages to copy in the next step.
+   */
+  async backup(filename, options) {
+    if (options == null) options = {};
+    if (typeof filename !== 'string') throw new TypeError('Expected first argument to be a string');
+    if (typeof options !== 'object') throw new TypeError('Expected second argument to be an options object');
+    filename = filename.trim();
+    const attachedName = 'attached' in options ? options.attached : 'main';
+    const handler = 'progress' in options ? options.progress : null;
+    if (!filename) throw new TypeError('Backup filename cannot be an empty string');
+    if (filename === ':memory:') throw new TypeError('Invalid backup filename ":memory:"');
+    if (typeof attachedName !== 'string') throw new TypeError('Expected the "attached" option to be a string');
+    if (!attachedName) throw new TypeError('The "attached" option cannot be an empty string');
+    if (handler != null && typeof handler !== 'function') throw new TypeError('Expected the "progress" option to be a function');
+    await fs.promises.access(path.dirname(filename)).catch(() => {
+      throw new TypeError('Cannot save backup because the directory does not exist');
+    });
+    const isNewFile = await fs.promises.access(filename).then(() => false, () => true);
+    let backup;
+    try {
+      backup = this.db.backup(attachedName, filename);
+    } catch (err) {
+      if (isNewFile) fs.rmSync(filename, { force: true });
+      throw err;
+    }
+    return runBackup(backup, handler, isNewFile ? filename : null);
   }
 
+  /**
+   * Returns the contents of the database as a buffer, which can be opened
+   * again with `new Database(buffer)`.
+   *
+   * @param {object} [options] - The options for serialization.
+   * @param {string} [options.attached] - The name of the attached database to serialize.
+   */
   serialize(options) {
-    throw new Error("not implemented");
+    if (options == null) options = {};
+    if (typeof options !== 'object') throw new TypeError('Expected first argument to be an options object');
+    const attachedName = 'attached' in options ? options.attached : 'main';
+    if (typeof attachedName !== 'string') throw new TypeError('Expected the "attached" option to be a string');
+    if (!attachedName) throw new TypeError('The "attached" option cannot be an empty string');
+    return this.db.serialize(attachedName);
   }
 
+  /**
+   * Registers a user-defined function that can be called from SQL.
+   *
+   * @param {string} name - The name of the function.
+   * @param {object} [options] - The options for the function.
+   * @param {function} fn - The function to call.
+   */
   function(name, options, fn) {
-    throw new Error("not implemented");
+    if (options == null) options = {};
+    if (typeof options === 'function') {
+      fn = options;
+      options = {};
+    }
+    if (typeof name !== 'string') throw new TypeError('Expected first argument to be a string');
+    if (typeof fn !== 'function') throw new TypeError('Expected last argument to be a function');
+    if (typeof options !== 'object') throw new TypeError('Expected second argument to be an options object');
+    if (!name) throw new TypeError('User-defined function name cannot be an empty string');
+    this.db.function(name, options, fn);
+    return this;
   }
 
+  /**
+   * Registers a user-defined aggregate function that can be called from SQL.
+   *
+   * @param {string} name - The name of the aggregate function.
+   * @param {object} options - The options for the aggregate function.
+   */
   aggregate(name, options) {
-    throw new Error("not implemented");
+    if (typeof name !== 'string') throw new TypeError('Expected first argument to be a string');
+    if (typeof options !== 'object' || options === null) throw new TypeError('Expected second argument to be an options object');
+    if (!name) throw new TypeError('User-defined function name cannot be an empty string');
+    const start = 'start' in options ? options.start : null;
+    const step = getFunctionOption(options, 'step', true);
+    const inverse = getFunctionOption(options, 'inverse', false);
+    const result = getFunctionOption(options, 'result', false);
+    this.db.aggregate(name, options, start, step, inverse, result);
+    return this;
   }
 
+  /**
+   * Registers a virtual table module whose rows are produced by a generator function.
+   *
+   * @param {string} name - The name of the virtual table module.
+   * @param {object|function} factory - The table definition, or a function returning
+   * one for the arguments of each `CREATE VIRTUAL TABLE` using the module.
+   */
   table(name, factory) {
-    throw new Error("not implemented");
+    if (typeof name !== 'string') throw new TypeError('Expected first argument to be a string');
+    if (!name) throw new TypeError('Virtual table module name cannot be an empty string');
+    let eponymous = false;
+    if (typeof factory === 'object' && factory !== null) {
+      eponymous = true;
+      const definition = parseTableDefinition(factory, 'used', name);
+      factory = () => definition;
+    } else {
+      if (typeof factory !== 'function') throw new TypeError('Expected second argument to be a function or a table definition object');
+      factory = wrapTableFactory(factory);
+    }
+    this.db.table(name, factory, eponymous);
+    return this;
   }
 
-  loadExtension(...args) {
-    throw new Error("not implemented");
+  /**
+   * Toggles loading extensions with `loadExtension()`, which is disabled by default.
+   *
+   * @param {boolean} [toggle] - Enable or disable extension loading. If you don't pass the parameter, extension loading is enabled.
+   */
+  enableLoadExtension(toggle) {
+    this.db.enableLoadExtension(toggle);
+    return this;
   }
 
+  /**
+   * Loads a SQLite extension.
+   *
+   * @param {string} path - Path to the extension shared library.
+   * @param {string} [entryPoint] - Name of the extension entry point.
+   */
+  loadExtension(path, entryPoint) {
+    if (typeof path !== 'string') throw new TypeError('Expected first argument to be a string');
+    if (entryPoint !== undefined && typeof entryPoint !== 'string') throw new TypeError('Expected second argument to be a string');
+    this.db.loadExtension(path, entryPoint);
+    return this;
+  }
+
+  /**
+   * Changes the encryption key of the database. Rekeying a plaintext database
+   * encrypts it, and an empty or null key decrypts the database.
+   *
+   * @param {string|Buffer|null} key - The new encryption key.
+   * @param {string} [cipher] - The cipher used with the new key.
+   */
+  rekey(key, cipher) {
+    if (key != null && typeof key !== 'string' && !Buffer.isBuffer(key)) {
+      throw new TypeError('Expected first argument to be a string, a Buffer or null');
+    }
+    if (cipher !== undefined && typeof cipher !== 'string') throw new TypeError('Expected second argument to be a string');
+    this.db.rekey(key, cipher);
+    return this;
+  }
+
+  /**
+   * Returns the highest replication index of the writes made through this
+   * database, or `null` if there are none.
+   */
   maxWriteReplicationIndex() {
-    throw new Error("not implemented");
+    return this.db.maxWriteReplicationIndex();
   }
 
   /**
@@ -148,11 +500,7 @@ class Database {
    * @param {string} sql - The SQL statement string to execute.
    */
   exec(sql) {
-    try {
-      this.db.exec(sql);
-    } catch (err) {
-      throw convertError(err);
-    }
+    this.db.exec(sql);
   }
 
   /**
@@ -169,8 +517,31 @@ class Database {
     this.db.close();
   }
 
-  authorizer(hook) {
-    this.db.authorizer(hook);
+  /**
+   * Sets a hook or policy that authorizes each action of the statements being prepared.
+   *
+   * @param {function|object|null} authorizer - A hook called with the action, table, column, database and accessor of each request, returning "allow", "deny" or "ignore", a policy of allow, deny and ignore rules evaluated natively, or null to remove the authorizer.
+   */
+  authorizer(authorizer) {
+    if (authorizer == null) authorizer = null;
+    else if (typeof authorizer !== 'function' && typeof authorizer !== 'object') throw new TypeError('Expected first argument to be a function, a policy object or null');
+    this.db.authorizer(authorizer);
+    return this;
+  }
+
+  /**
+   * Sets a hook that is called asynchronously with the statements run on the connection.
+   *
+   * @param {function|null} hook - Called with `{ type, sql, expandedSql, duration }` events, or null to remove the hook.
+   * @param {object} [options] - The events to report.
+   * @param {boolean} [options.statement] - Report each statement as it starts running, with its parameters expanded. Defaults to true.
+   * @param {boolean} [options.profile] - Report each statement as it finishes, with its duration in seconds. Defaults to true.
+   * @param {boolean} [options.row] - Report each row returned by a statement. Defaults to false.
+   */
+  trace(hook, options) {
+    if (hook != null && typeof hook !== 'function') throw new TypeError('Expected first argument to be a function or null');
+    if (options != null && typeof options !== 'object') throw new TypeError('Expected second argument to be an options object');
+    this.db.trace(hook == null ? null : hook, options);
     return this;
   }
 
@@ -215,19 +586,45 @@ class Statement {
     return this;
   }
 
+  /**
+   * The SQL text the statement was prepared from.
+   */
+  get source() {
+    return this.stmt.source;
+  }
+
+  /**
+   * Whether the statement returns data.
+   */
   get reader() {
-    throw new Error("not implemented");
+    return this.stmt.reader;
+  }
+
+  /**
+   * Whether the statement makes no direct changes to the database.
+   */
+  get readonly() {
+    return this.stmt.readonly;
   }
 
   /**
-   * Executes the SQL statement and returns an info object.
+   * Returns the SQL text of the statement with its parameters replaced by
+   * the bound parameters, or the given ones.
+   *
+   * @param bindParameters - The bind parameters to show in the SQL text.
+   */
+  expandedSql(...bindParameters) {
+    return this.stmt.expandedSql(...bindParameters);
+  }
+
+  /**
+   * Executes the SQL statement and returns an info object, which includes
+   * the returned rows for statements that return data.
+   *
+   * @param bindParameters - The bind parameters for executing the statement.
    */
   run(...bindParameters) {
-    try {
-      return this.stmt.run(...bindParameters);
-    } catch (err) {
-      throw convertError(err);
-    }
+    return this.stmt.run(...bindParameters);
   }
 
   /**
@@ -236,24 +633,34 @@ class Statement {
    * @param bindParameters - The bind parameters for executing the statement.
    */
   get(...bindParameters) {
-    try {
-      return this.stmt.get(...bindParameters);
-    } catch (err) {
-      throw convertError(err);
-    }
+    return this.stmt.get(...bindParameters);
   }
 
   /**
    * Executes the SQL statement and returns an iterator to the resulting rows.
+   * With `for await`, the rows are fetched off the main thread in batches.
    *
    * @param bindParameters - The bind parameters for executing the statement.
    */
   iterate(...bindParameters) {
-    try {
-      return this.stmt.iterate(...bindParameters);
-    } catch (err) {
-      throw convertError(err);
-    }
+    const iterator = this.stmt.iterate(...bindParameters);
+    return {
+      next() {
+        return iterator.next();
+      },
+      return(value) {
+        return iterator.return(value);
+      },
+      throw(err) {
+        return iterator.throw(err);
+      },
+      [Symbol.iterator]() {
+        return this;
+      },
+      [Symbol.asyncIterator]() {
+        return iterateAsync(iterator, ITERATE_BATCH_SIZE);
+      },
+    };
   }
 
   /**
@@ -262,11 +669,19 @@ class Statement {
    * @param bindParameters - The bind parameters for executing the statement.
    */
   all(...bindParameters) {
-    try {
-      return this.stmt.all(...bindParameters);
-    } catch (err) {
-      throw convertError(err);
-    }
+    return this.stmt.all(...bindParameters);
+  }
+
+  /**
+   * Permanently binds parameters to the statement. Executions then use them
+   * and throw if given parameters of their own.
+   *
+   * @param bindParameters - The bind parameters for every execution of the statement.
+   */
+  bind(...bindParameters) {
+    // Several parameters are bound by position, like an array of them.
+    this.stmt.bind(bindParameters.length > 1 ? bindParameters : bindParameters[0]);
+    return this;
   }
 
   /**
@@ -294,4 +709,6 @@ class Statement {
 }
 
 module.exports = Database;
+module.exports.Statement = Statement;
 module.exports.SqliteError = SqliteError;
+module.exports.OpenFlags = OpenFlags;